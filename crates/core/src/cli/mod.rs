@@ -0,0 +1,435 @@
+//! Amp CLI binary lookup, version detection and invocation
+//!
+//! Runs are tracked in `runs` so they can be cancelled from Lua.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    sync::RwLock,
+    time::Duration,
+};
+
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+use crate::errors::{AmpError, Result};
+
+pub mod runs;
+
+use runs::{Registration, RunId};
+
+/// Name of the Amp CLI executable
+pub const AMP_BINARY: &str = "amp";
+
+/// Version reported by `amp --version`, cached for the session
+///
+/// Only successful lookups are cached so installing the CLI mid-session is
+/// picked up on the next call.
+static VERSION: RwLock<Option<String>> = RwLock::new(None);
+
+/// Time limit for `amp --version`
+///
+/// `amp.version` blocks Neovim while it waits, so a CLI that hangs (auth
+/// prompt, network, broken shim) must not stall the editor for long.
+pub const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Amp CLI version, running `amp --version` on first call
+///
+/// Goes through `run`, so it is killed after `VERSION_TIMEOUT`. A missing
+/// binary is reported as `AmpCliNotFound`; a failing, hanging or
+/// unparseable one as `AmpCliError`.
+pub async fn version() -> Result<String> {
+    if let Some(version) = VERSION.read().unwrap_or_else(|e| e.into_inner()).clone() {
+        return Ok(version);
+    }
+
+    let output = run(RunRequest {
+        args: vec!["--version".to_string()],
+        timeout: Some(VERSION_TIMEOUT),
+        ..Default::default()
+    })
+    .await?;
+
+    if output.exit_code != Some(0) {
+        return Err(AmpError::AmpCliError(format!(
+            "'{} --version' exited with {}: {}",
+            AMP_BINARY,
+            output
+                .exit_code
+                .map_or_else(|| "a signal".to_string(), |code| format!("code {}", code)),
+            output.stderr.trim()
+        )));
+    }
+
+    let version = parse_version(&output.stdout).ok_or_else(|| {
+        AmpError::AmpCliError(format!(
+            "unrecognized version output: {}",
+            output.stdout.trim()
+        ))
+    })?;
+
+    *VERSION.write().unwrap_or_else(|e| e.into_inner()) = Some(version.clone());
+    Ok(version)
+}
+
+/// Forget the cached version; returns whether one was cached
+pub fn clear_version_cache() -> bool {
+    VERSION
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+        .is_some()
+}
+
+/// Default time limit for `amp.run`
+pub const DEFAULT_RUN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Upper bound on a caller-supplied `amp.run` timeout
+pub const MAX_RUN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Captured bytes per stream; anything beyond is discarded
+pub const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Maximum number of arguments accepted by `amp.run`
+pub const MAX_ARGS: usize = 64;
+
+/// Maximum length of a single argument in bytes
+pub const MAX_ARG_LEN: usize = 8 * 1024;
+
+/// A CLI invocation
+#[derive(Debug, Clone, Default)]
+pub struct RunRequest {
+    pub args: Vec<String>,
+    pub stdin: Option<String>,
+    pub cwd: Option<PathBuf>,
+    pub timeout: Option<Duration>,
+}
+
+/// Captured result of a CLI invocation
+#[derive(Debug, Clone, Serialize)]
+pub struct RunOutput {
+    pub run_id: RunId,
+    pub stdout: String,
+    pub stderr: String,
+    /// `None` when the process was terminated by a signal
+    pub exit_code: Option<i32>,
+    /// Whether stdout or stderr exceeded `MAX_OUTPUT_BYTES`
+    pub truncated: bool,
+}
+
+impl RunRequest {
+    /// Check argument limits and the working directory
+    ///
+    /// Arguments are passed directly to the process (no shell), so only
+    /// sizes and NUL bytes need checking.
+    pub fn validate(&self) -> Result<()> {
+        if self.args.len() > MAX_ARGS {
+            return Err(AmpError::ValidationError(format!(
+                "too many arguments ({}, max {})",
+                self.args.len(),
+                MAX_ARGS
+            )));
+        }
+        for arg in &self.args {
+            if arg.len() > MAX_ARG_LEN {
+                return Err(AmpError::ValidationError(format!(
+                    "argument longer than {} bytes",
+                    MAX_ARG_LEN
+                )));
+            }
+            if arg.contains('\0') {
+                return Err(AmpError::ValidationError(
+                    "arguments must not contain NUL bytes".to_string(),
+                ));
+            }
+        }
+        if let Some(cwd) = &self.cwd {
+            if !cwd.is_dir() {
+                return Err(AmpError::ValidationError(format!(
+                    "cwd is not a directory: {}",
+                    cwd.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the Amp CLI and wait for its output
+///
+/// The run is registered (see `runs`) for its whole duration, so it can be
+/// cancelled with `amp.cancel`. The process is killed if it outlives the
+/// timeout or is cancelled.
+pub async fn run(request: RunRequest) -> Result<RunOutput> {
+    request.validate()?;
+    let mut registration = runs::register();
+    execute(request, &mut registration).await
+}
+
+/// Start the Amp CLI in the background and return its run id
+///
+/// The result is collected with `runs::take_result` once the run finishes.
+pub fn start(request: RunRequest) -> Result<RunId> {
+    request.validate()?;
+    let mut registration = runs::register();
+    let id = registration.id;
+
+    crate::runtime::spawn(async move {
+        let result = execute(request, &mut registration).await;
+        // Store the result before the registration is dropped so the run is
+        // always either running or finished
+        runs::finish(id, result);
+        drop(registration);
+    });
+
+    Ok(id)
+}
+
+async fn execute(request: RunRequest, registration: &mut Registration) -> Result<RunOutput> {
+    let timeout = request
+        .timeout
+        .unwrap_or(DEFAULT_RUN_TIMEOUT)
+        .min(MAX_RUN_TIMEOUT);
+
+    let mut command = tokio::process::Command::new(AMP_BINARY);
+    command
+        .args(&request.args)
+        .stdin(if request.stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(cwd) = &request.cwd {
+        command.current_dir(cwd);
+    }
+    // Own process group so cancellation also stops anything the CLI spawned
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let mut child = command.spawn().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => AmpError::AmpCliNotFound(AMP_BINARY.to_string()),
+        _ => AmpError::AmpCliError(format!("failed to start '{}': {}", AMP_BINARY, e)),
+    })?;
+
+    // Feed stdin from a task so a child that doesn't read it can't block us
+    if let (Some(input), Some(mut pipe)) = (request.stdin, child.stdin.take()) {
+        tokio::spawn(async move {
+            let _ = pipe.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    let collect = async {
+        let (stdout, stderr, status) = tokio::join!(
+            read_capped(stdout, MAX_OUTPUT_BYTES),
+            read_capped(stderr, MAX_OUTPUT_BYTES),
+            child.wait()
+        );
+        Ok::<_, AmpError>((stdout?, stderr?, status?))
+    };
+
+    // `None` when cancelled
+    let outcome = tokio::select! {
+        result = tokio::time::timeout(timeout, collect) => Some(result),
+        _ = &mut registration.cancelled => None,
+    };
+
+    let ((stdout, stdout_truncated), (stderr, stderr_truncated), status) = match outcome {
+        Some(Ok(result)) => result?,
+        Some(Err(_)) => {
+            kill(&mut child);
+            return Err(AmpError::AmpCliError(format!(
+                "'{} {}' timed out after {}s",
+                AMP_BINARY,
+                request.args.join(" "),
+                timeout.as_secs()
+            )));
+        },
+        None => {
+            kill(&mut child);
+            return Err(AmpError::AmpCliError(format!(
+                "run {} was cancelled",
+                registration.id
+            )));
+        },
+    };
+
+    Ok(RunOutput {
+        run_id: registration.id,
+        stdout: String::from_utf8_lossy(&stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr).into_owned(),
+        exit_code: status.code(),
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+/// Kill a run's process (and its process group on unix)
+fn kill(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: killpg has no memory-safety preconditions; the group id is
+        // the child's pid because it was spawned with process_group(0)
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    let _ = child.start_kill();
+}
+
+/// Read a stream to the end, keeping at most `max` bytes
+///
+/// The rest is drained and discarded so the child never blocks on a full
+/// pipe. Returns the captured bytes and whether anything was dropped.
+async fn read_capped<R: AsyncRead + Unpin>(mut reader: R, max: usize) -> Result<(Vec<u8>, bool)> {
+    let mut captured = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 8192];
+
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        let room = max.saturating_sub(captured.len());
+        if n > room {
+            truncated = true;
+        }
+        captured.extend_from_slice(&buf[..n.min(room)]);
+    }
+
+    Ok((captured, truncated))
+}
+
+/// Extract the version from `amp --version` output
+///
+/// Takes the first whitespace-separated token starting with a digit,
+/// without a leading `v` (e.g. `amp v0.0.17` or `0.0.17-abc (released ...)`).
+fn parse_version(output: &str) -> Option<String> {
+    output
+        .split_whitespace()
+        .map(|token| token.trim_start_matches('v'))
+        .find(|token| token.starts_with(|c: char| c.is_ascii_digit()))
+        .map(String::from)
+}
+
+/// Find an executable on `PATH`, like `which`
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .flat_map(|dir| candidates(&dir, name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(windows)]
+fn candidates(dir: &Path, name: &str) -> Vec<PathBuf> {
+    ["", ".exe", ".cmd"]
+        .iter()
+        .map(|ext| dir.join(format!("{}{}", name, ext)))
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn candidates(dir: &Path, name: &str) -> Vec<PathBuf> {
+    vec![dir.join(name)]
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_in_path_finds_shell() {
+        assert!(find_in_path("sh").is_some());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("0.0.17\n").as_deref(), Some("0.0.17"));
+        assert_eq!(parse_version("amp v1.2.3").as_deref(), Some("1.2.3"));
+        assert_eq!(
+            parse_version("0.0.1760000000-g1a2b3c (released 2025-10-01)").as_deref(),
+            Some("0.0.1760000000-g1a2b3c")
+        );
+        assert_eq!(parse_version("unknown"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn test_validate_limits_args() {
+        let ok = RunRequest {
+            args: vec!["threads".into(), "list".into()],
+            ..Default::default()
+        };
+        assert!(ok.validate().is_ok());
+
+        let too_many = RunRequest {
+            args: vec!["x".into(); MAX_ARGS + 1],
+            ..Default::default()
+        };
+        assert!(too_many.validate().is_err());
+
+        let too_long = RunRequest {
+            args: vec!["x".repeat(MAX_ARG_LEN + 1)],
+            ..Default::default()
+        };
+        assert!(too_long.validate().is_err());
+
+        let nul = RunRequest {
+            args: vec!["a\0b".into()],
+            ..Default::default()
+        };
+        assert!(nul.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_cwd() {
+        let request = RunRequest {
+            cwd: Some(PathBuf::from("/definitely/not/a/dir")),
+            ..Default::default()
+        };
+        assert!(request.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_capped_truncates_and_drains() {
+        let data = vec![b'x'; 20_000];
+        let (captured, truncated) = read_capped(&data[..], 10_000).await.unwrap();
+        assert_eq!(captured.len(), 10_000);
+        assert!(truncated);
+
+        let (captured, truncated) = read_capped(&b"short"[..], 10_000).await.unwrap();
+        assert_eq!(captured, b"short");
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_clear_version_cache() {
+        *VERSION.write().unwrap() = Some("1.0.0".to_string());
+        assert!(clear_version_cache());
+        assert!(!clear_version_cache());
+    }
+
+    #[test]
+    fn test_find_in_path_missing_binary() {
+        assert!(find_in_path("amp-extras-definitely-not-installed").is_none());
+    }
+}
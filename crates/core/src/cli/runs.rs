@@ -0,0 +1,199 @@
+//! Registry of in-flight Amp CLI runs
+//!
+//! Every run gets an id when it starts. `cancel` signals the run to kill its
+//! process and marks it cancelling; the registry entry is removed when the
+//! run ends for any reason (its `Registration` is dropped). Background runs
+//! park their result here before that, until Lua collects it with
+//! `take_result`, so a run is always running, cancelling or finished.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use once_cell::sync::Lazy;
+use tokio::sync::oneshot;
+
+use super::RunOutput;
+use crate::errors::Result;
+
+/// Identifier of a CLI run
+pub type RunId = u64;
+
+/// Finished background results kept for collection
+///
+/// Oldest results are dropped beyond this, so uncollected runs can't grow
+/// without bound.
+const MAX_FINISHED: usize = 32;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Cancellation senders of running runs; `None` once cancelled (cancelling)
+static RUNNING: Lazy<Mutex<HashMap<RunId, Option<oneshot::Sender<()>>>>> =
+    Lazy::new(Default::default);
+
+/// Results of finished background runs, by id (ids increase over time)
+static FINISHED: Lazy<Mutex<BTreeMap<RunId, Result<RunOutput>>>> = Lazy::new(Default::default);
+
+/// A registered run; unregisters itself on drop
+pub struct Registration {
+    pub id: RunId,
+    /// Resolves when the run is cancelled
+    pub cancelled: oneshot::Receiver<()>,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        RUNNING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.id);
+    }
+}
+
+/// Register a new run
+pub fn register() -> Registration {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let (tx, rx) = oneshot::channel();
+    RUNNING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id, Some(tx));
+
+    Registration { id, cancelled: rx }
+}
+
+/// Cancel a running run; returns whether it was running (and not already
+/// cancelling)
+///
+/// The run stays registered, as cancelling, until it has stopped.
+pub fn cancel(id: RunId) -> bool {
+    let sender = RUNNING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_mut(&id)
+        .and_then(Option::take);
+
+    match sender {
+        Some(tx) => {
+            let _ = tx.send(());
+            true
+        },
+        None => false,
+    }
+}
+
+/// Whether a run is still running (including while cancelling)
+pub fn is_running(id: RunId) -> bool {
+    RUNNING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains_key(&id)
+}
+
+/// Whether a run was cancelled but hasn't stopped yet
+pub fn is_cancelling(id: RunId) -> bool {
+    RUNNING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&id)
+        .is_some_and(Option::is_none)
+}
+
+/// Ids of all running runs, ascending
+pub fn running() -> Vec<RunId> {
+    let mut ids: Vec<_> = RUNNING
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .keys()
+        .copied()
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// Store the result of a finished background run
+pub fn finish(id: RunId, result: Result<RunOutput>) {
+    let mut finished = FINISHED.lock().unwrap_or_else(|e| e.into_inner());
+    finished.insert(id, result);
+    while finished.len() > MAX_FINISHED {
+        finished.pop_first();
+    }
+}
+
+/// Take the result of a finished background run
+pub fn take_result(id: RunId) -> Option<Result<RunOutput>> {
+    FINISHED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::AmpError;
+
+    #[test]
+    fn test_registration_lifecycle() {
+        let registration = register();
+        let id = registration.id;
+        assert!(is_running(id));
+        assert!(running().contains(&id));
+
+        drop(registration);
+        assert!(!is_running(id));
+        assert!(!cancel(id));
+    }
+
+    #[test]
+    fn test_ids_are_unique() {
+        let a = register();
+        let b = register();
+        assert_ne!(a.id, b.id);
+    }
+
+    #[test]
+    fn test_cancel_signals_and_marks_cancelling() {
+        let mut registration = register();
+        let id = registration.id;
+        assert!(!is_cancelling(id));
+
+        assert!(cancel(id));
+        assert!(registration.cancelled.try_recv().is_ok());
+        // Still registered until the run stops
+        assert!(is_running(id));
+        assert!(is_cancelling(id));
+
+        // Cancelling twice is a no-op
+        assert!(!cancel(id));
+
+        drop(registration);
+        assert!(!is_running(id));
+        assert!(!is_cancelling(id));
+    }
+
+    #[test]
+    fn test_finished_results_are_taken_once() {
+        let id = register().id;
+        finish(id, Err(AmpError::AmpCliError("boom".to_string())));
+
+        assert!(take_result(id).unwrap().is_err());
+        assert!(take_result(id).is_none());
+    }
+
+    #[test]
+    fn test_finished_results_are_bounded() {
+        let ids: Vec<_> = (0..MAX_FINISHED + 5).map(|_| register().id).collect();
+        for &id in &ids {
+            finish(id, Err(AmpError::Other("x".to_string())));
+        }
+
+        // The oldest results were dropped
+        assert!(take_result(ids[0]).is_none());
+        assert!(take_result(*ids.last().unwrap()).is_some());
+    }
+}
@@ -0,0 +1,326 @@
+//! Curated user-facing actions for a command palette
+//!
+//! Unlike `commands.list` (every registered command) and `meta::COMMANDS`
+//! (their arguments), this is the short list of things a user would pick
+//! from a palette. Most actions are the plugin's `:Amp*` user commands; a
+//! few call core commands directly. Ids match the keymap action names in
+//! `lua/amp_extras/init.lua` so Lua can attach keymap hints.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::errors::Result;
+
+/// How an action is invoked
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Invoke {
+    /// Run an Ex command (`vim.cmd(command)`)
+    Ex { command: &'static str },
+    /// Call a core command with default args (`ffi.call(command, args)`)
+    Core {
+        command: &'static str,
+        /// Default arguments as JSON
+        args: &'static str,
+    },
+}
+
+/// A palette entry
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Action {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub category: &'static str,
+    /// Only meaningful with an active visual selection
+    pub needs_selection: bool,
+    /// Only meaningful with an Amp CLI connected to amp.nvim
+    pub needs_client: bool,
+    pub invoke: Invoke,
+}
+
+const fn ex(command: &'static str) -> Invoke {
+    Invoke::Ex { command }
+}
+
+/// All palette actions, in display order
+pub const ACTIONS: &[Action] = &[
+    Action {
+        id: "send_selection",
+        title: "Send Selection",
+        description: "Send the selected text to the Amp prompt",
+        category: "send",
+        needs_selection: true,
+        needs_client: true,
+        invoke: ex("'<,'>AmpSendSelection"),
+    },
+    Action {
+        id: "send_selection_ref",
+        title: "Send Selection Reference",
+        description: "Send a reference to the selected lines (@file.rs#L10-L20)",
+        category: "send",
+        needs_selection: true,
+        needs_client: true,
+        invoke: ex("'<,'>AmpSendSelectionRef"),
+    },
+    Action {
+        id: "send_buffer",
+        title: "Send Buffer",
+        description: "Send the whole buffer to the Amp prompt",
+        category: "send",
+        needs_selection: false,
+        needs_client: true,
+        invoke: ex("AmpSendBuffer"),
+    },
+    Action {
+        id: "send_file_ref",
+        title: "Send File Reference",
+        description: "Send a reference to the current file (@file.rs)",
+        category: "send",
+        needs_selection: false,
+        needs_client: true,
+        invoke: ex("AmpSendFileRef"),
+    },
+    Action {
+        id: "send_line_ref",
+        title: "Send Line Reference",
+        description: "Send a reference to the current line (@file.rs#L10)",
+        category: "send",
+        needs_selection: false,
+        needs_client: true,
+        invoke: ex("AmpSendLineRef"),
+    },
+    Action {
+        id: "send_message",
+        title: "Send Message",
+        description: "Type a message for the Amp prompt",
+        category: "send",
+        needs_selection: false,
+        needs_client: true,
+        invoke: ex("AmpSendMessage"),
+    },
+    Action {
+        id: "compose",
+        title: "Compose Message",
+        description: "Write a message in a markdown buffer; :w sends it",
+        category: "send",
+        needs_selection: false,
+        needs_client: true,
+        invoke: ex("AmpCompose"),
+    },
+    Action {
+        id: "dashx_list",
+        title: "List Prompts",
+        description: "Browse, edit and send saved prompts",
+        category: "prompts",
+        needs_selection: false,
+        needs_client: false,
+        invoke: ex("AmpDashX"),
+    },
+    Action {
+        id: "dashx_execute",
+        title: "Execute Prompt",
+        description: "Run a saved prompt in a floating session",
+        category: "prompts",
+        needs_selection: false,
+        needs_client: false,
+        invoke: ex("AmpExecute"),
+    },
+    Action {
+        id: "session_new",
+        title: "New Session",
+        description: "Start an interactive Amp session",
+        category: "session",
+        needs_selection: false,
+        needs_client: false,
+        invoke: ex("AmpSession"),
+    },
+    Action {
+        id: "session_msg",
+        title: "New Session with Message",
+        description: "Start an interactive Amp session with an initial message",
+        category: "session",
+        needs_selection: false,
+        needs_client: false,
+        invoke: ex("AmpSessionWithMessage"),
+    },
+    Action {
+        id: "login",
+        title: "Log In",
+        description: "Log in to Amp",
+        category: "account",
+        needs_selection: false,
+        needs_client: false,
+        invoke: ex("AmpLogin"),
+    },
+    Action {
+        id: "logout",
+        title: "Log Out",
+        description: "Log out from Amp",
+        category: "account",
+        needs_selection: false,
+        needs_client: false,
+        invoke: ex("AmpLogout"),
+    },
+    Action {
+        id: "update",
+        title: "Update Amp CLI",
+        description: "Update the Amp CLI to the latest version",
+        category: "account",
+        needs_selection: false,
+        needs_client: false,
+        invoke: ex("AmpUpdate"),
+    },
+    Action {
+        id: "amp_version",
+        title: "Amp CLI Version",
+        description: "Show the installed Amp CLI version",
+        category: "plugin",
+        needs_selection: false,
+        needs_client: false,
+        invoke: Invoke::Core {
+            command: "amp.version",
+            args: "{}",
+        },
+    },
+    Action {
+        id: "reload",
+        title: "Reload amp-extras",
+        description: "Reload the plugin's Lua modules and core state",
+        category: "plugin",
+        needs_selection: false,
+        needs_client: false,
+        invoke: ex("AmpReload"),
+    },
+];
+
+/// List palette actions: `{selection_active?, client_connected?}` ->
+/// `{actions: [{id, title, description, category, needs_selection,
+/// needs_client, invoke, available}]}`
+///
+/// Lua passes the live editor state; `available` is false for actions whose
+/// requirements it doesn't meet, so the palette can grey them out. Missing
+/// flags count as false.
+pub fn list(args: Value) -> Result<Value> {
+    let flag = |name: &str| args.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+    let selection_active = flag("selection_active");
+    let client_connected = flag("client_connected");
+
+    let actions: Vec<Value> = ACTIONS
+        .iter()
+        .map(|action| {
+            let available = (!action.needs_selection || selection_active)
+                && (!action.needs_client || client_connected);
+
+            let mut item = json!(action);
+            if let Invoke::Core { args, .. } = action.invoke {
+                item["invoke"]["args"] = serde_json::from_str(args).unwrap_or(Value::Null);
+            }
+            item["available"] = json!(available);
+            item
+        })
+        .collect();
+
+    Ok(json!({ "actions": actions }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Lua sources that define the plugin's user commands
+    fn user_command_sources() -> String {
+        let lua = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../lua/amp_extras");
+        ["commands/init.lua", "commands/dashx/init.lua"]
+            .iter()
+            .map(|file| std::fs::read_to_string(lua.join(file)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_action_ids_are_unique() {
+        let ids: HashSet<_> = ACTIONS.iter().map(|a| a.id).collect();
+        assert_eq!(ids.len(), ACTIONS.len());
+    }
+
+    #[test]
+    fn test_core_actions_reference_registered_commands() {
+        let registered = crate::commands::list_commands();
+        for action in ACTIONS {
+            if let Invoke::Core { command, args } = action.invoke {
+                assert!(
+                    registered.iter().any(|c| c == command),
+                    "action '{}' calls unregistered command '{}'",
+                    action.id,
+                    command
+                );
+                assert!(
+                    serde_json::from_str::<Value>(args).is_ok_and(|v| v.is_object()),
+                    "action '{}' has invalid default args",
+                    action.id
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ex_actions_reference_user_commands() {
+        let sources = user_command_sources();
+        for action in ACTIONS {
+            if let Invoke::Ex { command } = action.invoke {
+                let name = command.trim_start_matches("'<,'>");
+                assert!(
+                    sources.contains(&format!("nvim_create_user_command(\"{}\"", name)),
+                    "action '{}' runs unknown user command '{}'",
+                    action.id,
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_availability_follows_editor_state() {
+        let available = |args: Value, id: &str| {
+            list(args).unwrap()["actions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|a| a["id"] == id)
+                .unwrap()["available"]
+                .as_bool()
+                .unwrap()
+        };
+
+        assert!(!available(json!({}), "send_selection"));
+        assert!(!available(
+            json!({ "selection_active": true }),
+            "send_selection"
+        ));
+        assert!(available(
+            json!({ "selection_active": true, "client_connected": true }),
+            "send_selection"
+        ));
+        assert!(!available(
+            json!({ "client_connected": false }),
+            "send_buffer"
+        ));
+        assert!(available(json!({}), "dashx_list"));
+    }
+
+    #[test]
+    fn test_core_invoke_args_are_objects() {
+        let result = list(json!({})).unwrap();
+        let version = result["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["id"] == "amp_version")
+            .unwrap();
+        assert_eq!(version["invoke"]["kind"], "core");
+        assert_eq!(version["invoke"]["args"], json!({}));
+    }
+}
@@ -0,0 +1,204 @@
+use std::{path::PathBuf, time::Duration};
+
+use serde_json::{json, Value};
+
+use crate::{
+    cli::{
+        self,
+        runs::{self, RunId},
+        RunRequest,
+    },
+    errors::{AmpError, Result},
+    runtime,
+};
+
+/// Amp CLI version: `{}` -> `{version}`
+///
+/// Runs `amp --version` once per session, giving up after
+/// `cli::VERSION_TIMEOUT`. Fails with category `amp_cli_not_found` when the
+/// binary is missing and `amp_cli` when it exits with an error or times out.
+pub fn version(_args: Value) -> Result<Value> {
+    let version = runtime::block_on(cli::version())?;
+    Ok(json!({ "version": version }))
+}
+
+/// Run the Amp CLI: `{args, stdin?, cwd?, timeout_ms?, wait?}` -> `{run_id, background}`
+///
+/// The run starts on the runtime and this returns `{run_id, background =
+/// true}` at once; collect the output with `amp.result` or stop it with
+/// `amp.cancel`. With `wait = true` the call blocks until the CLI exits
+/// and returns `{run_id, stdout, stderr, exit_code, truncated}` instead;
+/// that blocks Neovim for up to the timeout and can't be cancelled, so
+/// only use it for short commands.
+///
+/// A non-zero exit is returned as a result (check `exit_code`), not an
+/// error; timeouts, cancellation and a missing binary are errors.
+pub fn run(args: Value) -> Result<Value> {
+    let request = run_request(&args)?;
+
+    if args.get("wait").and_then(|v| v.as_bool()) == Some(true) {
+        let output = runtime::block_on(cli::run(request))?;
+        return Ok(json!(output));
+    }
+
+    let run_id = cli::start(request)?;
+    Ok(json!({ "run_id": run_id, "background": true }))
+}
+
+/// Cancel a run: `{run_id}` -> `{cancelled}`
+///
+/// Kills the run's process group. `cancelled` is false when the run already
+/// finished, is already being cancelled, or never existed.
+pub fn cancel(args: Value) -> Result<Value> {
+    let run_id = run_id(&args, "amp.cancel")?;
+    Ok(json!({ "cancelled": runs::cancel(run_id) }))
+}
+
+/// Result of a background run: `{run_id}` -> `{running = true, cancelling}`
+/// or the run output
+///
+/// A finished result can be taken once; the run's error (timeout,
+/// cancellation) is returned as the command error. A cancelled run reports
+/// `cancelling = true` until its process has stopped.
+pub fn result(args: Value) -> Result<Value> {
+    let run_id = run_id(&args, "amp.result")?;
+
+    if runs::is_running(run_id) {
+        return Ok(json!({
+            "run_id": run_id,
+            "running": true,
+            "cancelling": runs::is_cancelling(run_id),
+        }));
+    }
+
+    match runs::take_result(run_id) {
+        Some(output) => Ok(json!(output?)),
+        None => Err(AmpError::ValidationError(format!(
+            "No result for run {}",
+            run_id
+        ))),
+    }
+}
+
+/// Parse the `run_id` argument (`runId` is accepted too)
+fn run_id(args: &Value, command: &str) -> Result<RunId> {
+    args.get("run_id")
+        .or_else(|| args.get("runId"))
+        .and_then(|v| v.as_u64())
+        .ok_or_else(|| AmpError::InvalidArgs {
+            command: command.to_string(),
+            reason: "run_id must be a positive integer".to_string(),
+        })
+}
+
+/// Parse `amp.run` arguments
+fn run_request(args: &Value) -> Result<RunRequest> {
+    let invalid = |reason: &str| AmpError::InvalidArgs {
+        command: "amp.run".to_string(),
+        reason: reason.to_string(),
+    };
+
+    let cli_args = args
+        .get("args")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| invalid("args must be a list of strings"))?
+        .iter()
+        .map(|v| v.as_str().map(String::from))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid("args must be a list of strings"))?;
+
+    let stdin = match args.get("stdin") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(
+            v.as_str()
+                .ok_or_else(|| invalid("stdin must be a string"))?
+                .to_string(),
+        ),
+    };
+    let cwd = match args.get("cwd") {
+        None | Some(Value::Null) => None,
+        Some(v) => Some(PathBuf::from(
+            v.as_str().ok_or_else(|| invalid("cwd must be a string"))?,
+        )),
+    };
+    let timeout = match args.get("timeout_ms") {
+        None | Some(Value::Null) => None,
+        Some(v) => {
+            Some(Duration::from_millis(v.as_u64().ok_or_else(|| {
+                invalid("timeout_ms must be a positive integer")
+            })?))
+        },
+    };
+
+    Ok(RunRequest {
+        args: cli_args,
+        stdin,
+        cwd,
+        timeout,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_request_parses_all_fields() {
+        let request = run_request(&json!({
+            "args": ["threads", "list"],
+            "stdin": "hello",
+            "cwd": "/tmp",
+            "timeout_ms": 5000,
+        }))
+        .unwrap();
+
+        assert_eq!(request.args, vec!["threads", "list"]);
+        assert_eq!(request.stdin.as_deref(), Some("hello"));
+        assert_eq!(request.cwd, Some(PathBuf::from("/tmp")));
+        assert_eq!(request.timeout, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_run_request_rejects_bad_args() {
+        assert!(run_request(&json!({})).is_err());
+        assert!(run_request(&json!({ "args": "threads list" })).is_err());
+        assert!(run_request(&json!({ "args": ["threads", 1] })).is_err());
+        assert!(run_request(&json!({ "args": [], "timeout_ms": -1 })).is_err());
+    }
+
+    #[test]
+    fn test_cancel_unknown_run() {
+        let result = cancel(json!({ "run_id": u64::MAX })).unwrap();
+        assert_eq!(result["cancelled"], json!(false));
+
+        assert!(cancel(json!({})).is_err());
+        assert!(cancel(json!({ "run_id": "1" })).is_err());
+    }
+
+    #[test]
+    fn test_result_of_unknown_run() {
+        let err = result(json!({ "run_id": u64::MAX })).unwrap_err();
+        assert_eq!(err.category(), "validation");
+    }
+
+    #[test]
+    fn test_result_while_running() {
+        let registration = runs::register();
+        let value = result(json!({ "run_id": registration.id })).unwrap();
+        assert_eq!(value["running"], json!(true));
+        assert_eq!(value["cancelling"], json!(false));
+    }
+
+    #[test]
+    fn test_result_while_cancelling() {
+        let registration = runs::register();
+        assert_eq!(
+            cancel(json!({ "runId": registration.id })).unwrap()["cancelled"],
+            json!(true)
+        );
+
+        let value = result(json!({ "runId": registration.id })).unwrap();
+        assert_eq!(value["running"], json!(true));
+        assert_eq!(value["cancelling"], json!(true));
+    }
+}
@@ -0,0 +1,88 @@
+//! Session context shared with the Lua UI
+//!
+//! Tracks the active thread: `threads.create` sets it, `context.get` and
+//! `context.set` expose it to Lua. Sends go through amp.nvim's message API,
+//! which has no thread parameter, so they don't carry this id (see the
+//! README's "Active thread" section).
+
+use std::sync::RwLock;
+
+use serde_json::{json, Value};
+
+use super::threads;
+use crate::errors::{AmpError, Result};
+
+/// Thread the UI is chatting with (`None` until one is chosen)
+///
+/// Lives for the whole Neovim session: `plugin.reload_prepare` keeps it, so
+/// `:AmpReload` doesn't lose the active thread.
+static ACTIVE_THREAD: RwLock<Option<String>> = RwLock::new(None);
+
+/// Active thread id, if any
+pub fn active_thread() -> Option<String> {
+    ACTIVE_THREAD
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Replace the active thread; returns the previous one
+pub(super) fn set_active_thread(thread_id: Option<String>) -> Option<String> {
+    std::mem::replace(
+        &mut *ACTIVE_THREAD.write().unwrap_or_else(|e| e.into_inner()),
+        thread_id,
+    )
+}
+
+/// Current context: `{}` -> `{thread_id}` (`nil` when no thread is active)
+pub fn get(_args: Value) -> Result<Value> {
+    Ok(json!({ "thread_id": active_thread() }))
+}
+
+/// Set the active thread: `{thread_id}` -> `{thread_id, previous}`
+///
+/// `thread_id = nil` (or omitted) clears it. Ids must look like Amp thread
+/// ids (`T-<uuid>`).
+pub fn set(args: Value) -> Result<Value> {
+    let thread_id = match args.get("thread_id") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(id)) if threads::is_thread_id(id) => Some(id.clone()),
+        Some(_) => {
+            return Err(AmpError::InvalidArgs {
+                command: "context.set".to_string(),
+                reason: "thread_id must be a thread id (T-<uuid>) or nil".to_string(),
+            })
+        },
+    };
+
+    let previous = set_active_thread(thread_id.clone());
+    Ok(json!({ "thread_id": thread_id, "previous": previous }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_and_clear() {
+        let id = "T-5928a90d-d53b-488f-a829-4e36442142ee";
+        let before = active_thread();
+
+        let result = set(json!({ "thread_id": id })).unwrap();
+        assert_eq!(result["thread_id"], id);
+        assert_eq!(get(json!({})).unwrap()["thread_id"], id);
+
+        let result = set(json!({ "thread_id": null })).unwrap();
+        assert_eq!(result["previous"], id);
+        assert_eq!(get(json!({})).unwrap()["thread_id"], Value::Null);
+
+        set_active_thread(before);
+    }
+
+    #[test]
+    fn test_set_rejects_malformed_ids() {
+        assert!(set(json!({ "thread_id": "not-a-thread" })).is_err());
+        assert!(set(json!({ "thread_id": "T-5928a90d" })).is_err());
+        assert!(set(json!({ "thread_id": 42 })).is_err());
+    }
+}
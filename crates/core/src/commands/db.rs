@@ -0,0 +1,27 @@
+use std::{future::Future, pin::Pin};
+
+use serde_json::{json, Value};
+
+use crate::{db::Db, errors::Result, runtime};
+
+/// Open and migrate the database in the background
+///
+/// Setup only records the database path, so users who prefer eager
+/// initialization can call this once the UI is up instead of paying the
+/// cost on the first prompts command.
+pub fn warmup(_args: Value) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
+    Box::pin(async {
+        Db::pool().await?;
+        Ok(())
+    })
+}
+
+/// Replace a damaged database: `{}` -> `{path, backup}`
+///
+/// The fix suggested by schema `MigrationError`s. The old file is moved
+/// aside (`backup`, `nil` when there was none) and a new, empty database is
+/// created at `path`. See `Db::rebuild`.
+pub fn rebuild(_args: Value) -> Result<Value> {
+    let backup = runtime::block_on(Db::rebuild())?;
+    Ok(json!({ "path": Db::path(), "backup": backup }))
+}
@@ -0,0 +1,110 @@
+use serde_json::{json, Map, Value};
+
+use crate::{
+    errors::{AmpError, Result},
+    format::{self, Template},
+};
+
+/// Render a configured template
+///
+/// Args: `{template: "selection" | "diagnostic" | "file_ref", values: {...}}`
+pub fn render(args: Value) -> Result<Value> {
+    let name = args
+        .get("template")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing template")?;
+    let values = values_arg("format.render", &args, "values")?;
+
+    let text = format::render(name, &values)?;
+    Ok(json!({ "text": text }))
+}
+
+/// Preview a template against sample values
+///
+/// Args: `{template_name, sample: {...}, template?: string}`
+///
+/// When `template` is given it is validated and rendered instead of the
+/// configured one, so a config UI can preview edits before saving them.
+/// Validation failures are returned as `{valid = false, message}` rather
+/// than as errors.
+pub fn preview(args: Value) -> Result<Value> {
+    let name = args
+        .get("template_name")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing template_name")?;
+    let sample = values_arg("format.preview", &args, "sample")?;
+
+    let text = match args.get("template").and_then(|v| v.as_str()) {
+        Some(source) => match Template::parse(name, source) {
+            Ok(template) => template.render(&sample),
+            Err(e) => return Ok(json!({ "valid": false, "message": e.to_string() })),
+        },
+        None => format::render(name, &sample)?,
+    };
+
+    Ok(json!({ "valid": true, "text": text }))
+}
+
+/// Extract an optional object argument, defaulting to an empty map
+fn values_arg(command: &str, args: &Value, key: &str) -> Result<Map<String, Value>> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(Map::new()),
+        Some(Value::Object(map)) => Ok(map.clone()),
+        Some(_) => Err(AmpError::InvalidArgs {
+            command: command.to_string(),
+            reason: format!("{} must be a table", key),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_render_default_file_ref() {
+        let result = render(json!({
+            "template": "file_ref",
+            "values": {"path": "a.rs", "range": "#L3"}
+        }))
+        .unwrap();
+        assert_eq!(result["text"], json!("@a.rs#L3"));
+    }
+
+    #[test]
+    fn test_render_unknown_template() {
+        assert!(render(json!({"template": "nope", "values": {}})).is_err());
+    }
+
+    #[test]
+    fn test_preview_custom_template() {
+        let result = preview(json!({
+            "template_name": "selection",
+            "template": "```{{filetype}}\n{{content}}\n```",
+            "sample": {"filetype": "rust", "content": "let x = 1;"}
+        }))
+        .unwrap();
+        assert_eq!(result["valid"], json!(true));
+        assert_eq!(result["text"], json!("```rust\nlet x = 1;\n```"));
+    }
+
+    #[test]
+    fn test_preview_invalid_template() {
+        let result = preview(json!({
+            "template_name": "selection",
+            "template": "{{bogus}}",
+            "sample": {}
+        }))
+        .unwrap();
+        assert_eq!(result["valid"], json!(false));
+        assert!(result["message"].as_str().unwrap().contains("bogus"));
+    }
+
+    #[test]
+    fn test_preview_rejects_non_table_sample() {
+        let result = preview(json!({"template_name": "selection", "sample": 1}));
+        assert!(matches!(result, Err(AmpError::InvalidArgs { .. })));
+    }
+}
@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{cli, db::Db, errors::Result, runtime};
+
+/// Result of a single health check
+#[derive(Debug, Clone, Serialize)]
+struct Check {
+    name: &'static str,
+    ok: bool,
+    detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, result: std::result::Result<String, String>) -> Self {
+        match result {
+            Ok(detail) => Self {
+                name,
+                ok: true,
+                detail,
+            },
+            Err(detail) => Self {
+                name,
+                ok: false,
+                detail,
+            },
+        }
+    }
+}
+
+/// Run diagnostic checks: `{}` -> `{checks: [{name, ok, detail}]}`
+///
+/// Rendered by the Lua `:checkhealth amp_extras` provider. Opens the
+/// database if it isn't open yet.
+pub fn health(_args: Value) -> Result<Value> {
+    let checks = vec![
+        Check::new("database_dir", check_database_dir()),
+        Check::new("database", check_database()),
+        Check::new("amp_cli", check_amp_cli()),
+    ];
+    Ok(json!({ "checks": checks }))
+}
+
+/// The database directory exists (or can be created) and is writable
+fn check_database_dir() -> std::result::Result<String, String> {
+    let path = Db::path().ok_or("Database not configured (call setup first)")?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let probe = dir.join(format!(".amp-extras-health-{}", std::process::id()));
+    std::fs::write(&probe, b"").map_err(|e| format!("{} is not writable: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+
+    Ok(format!("{} is writable", dir.display()))
+}
+
+/// The database opens, migrates and answers a query
+fn check_database() -> std::result::Result<String, String> {
+    runtime::block_on(async {
+        let pool = Db::pool().await?;
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM prompts")
+            .fetch_one(&pool)
+            .await?;
+        Ok::<_, crate::errors::AmpError>(count)
+    })
+    .map(|count| {
+        let path = Db::path()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        format!("{} ({} prompts)", path, count)
+    })
+    .map_err(|e| e.user_message())
+}
+
+/// The Amp CLI is on PATH
+fn check_amp_cli() -> std::result::Result<String, String> {
+    cli::find_in_path(cli::AMP_BINARY)
+        .map(|path| path.display().to_string())
+        .ok_or_else(|| format!("'{}' not found on PATH", cli::AMP_BINARY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_reports_every_check() {
+        let result = health(json!({})).unwrap();
+        let checks = result["checks"].as_array().unwrap();
+
+        let names: Vec<_> = checks.iter().map(|c| c["name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["database_dir", "database", "amp_cli"]);
+
+        for check in checks {
+            assert!(check["ok"].is_boolean());
+            assert!(!check["detail"].as_str().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_check_amp_cli_detail_names_binary() {
+        if let Err(detail) = check_amp_cli() {
+            assert!(detail.contains("amp"));
+        }
+    }
+}
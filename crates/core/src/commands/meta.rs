@@ -0,0 +1,707 @@
+//! Command metadata and generated documentation
+//!
+//! `COMMANDS` describes every registered command (arguments, types, whether
+//! they are optional). `meta.generate` renders it into two checked-in
+//! artifacts:
+//!
+//! - `docs/commands.md`: Markdown reference grouped by category
+//! - `lua/amp_extras/meta/commands.lua`: lua-language-server annotations for
+//!   `ffi.call` argument tables
+//!
+//! Output is deterministic (sorted by name). A test fails when the checked-in
+//! files are stale or a registered command has no metadata; regenerate with
+//! `just gen-docs` (which runs that test with `AMP_EXTRAS_BLESS=1`).
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use crate::errors::Result;
+
+/// Note placed at the top of generated files
+const GENERATED_NOTE: &str =
+    "Generated by `meta.generate`; do not edit. Regenerate with `just gen-docs`.";
+
+/// A command argument
+#[derive(Debug, Clone, Copy)]
+pub struct Param {
+    pub name: &'static str,
+    /// Lua type as written in annotations (`string`, `integer`, `string[]`, ...)
+    pub ty: &'static str,
+    pub optional: bool,
+    pub description: &'static str,
+}
+
+/// Metadata for a registered command
+#[derive(Debug, Clone, Copy)]
+pub struct CommandMeta {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub params: &'static [Param],
+}
+
+/// Metadata for every registered command
+///
+/// Add an entry here when registering a new command.
+pub static COMMANDS: &[CommandMeta] = &[
+    CommandMeta {
+        name: "ping",
+        description: "Echo the arguments back with `pong = true`.",
+        params: &[],
+    },
+    CommandMeta {
+        name: "commands.list",
+        description: "List registered command names.",
+        params: &[Param {
+            name: "include_aliases",
+            ty: "boolean",
+            optional: true,
+            description: "Also list aliases, with `alias_of` and `deprecated`",
+        }],
+    },
+    CommandMeta {
+        name: "actions.list",
+        description: "Curated user-facing actions for a command palette, with live availability.",
+        params: &[
+            Param {
+                name: "selection_active",
+                ty: "boolean",
+                optional: true,
+                description: "A visual selection is active",
+            },
+            Param {
+                name: "client_connected",
+                ty: "boolean",
+                optional: true,
+                description: "An Amp CLI is connected to amp.nvim",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "amp.version",
+        description: "Amp CLI version from `amp --version`, cached for the session.",
+        params: &[],
+    },
+    CommandMeta {
+        name: "amp.run",
+        description: "Start the Amp CLI in the background; collect its output with `amp.result`.",
+        params: &[
+            Param {
+                name: "args",
+                ty: "string[]",
+                optional: false,
+                description: "CLI arguments (max 64, passed without a shell)",
+            },
+            Param {
+                name: "stdin",
+                ty: "string",
+                optional: true,
+                description: "Text written to the process's stdin",
+            },
+            Param {
+                name: "cwd",
+                ty: "string",
+                optional: true,
+                description: "Working directory",
+            },
+            Param {
+                name: "timeout_ms",
+                ty: "integer",
+                optional: true,
+                description: "Time limit (default 120000, max 600000)",
+            },
+            Param {
+                name: "wait",
+                ty: "boolean",
+                optional: true,
+                description:
+                    "Block until the CLI exits and return its output instead of `{ run_id }`",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "amp.cancel",
+        description: "Cancel an in-flight Amp CLI run, killing its process group.",
+        params: &[Param {
+            name: "run_id",
+            ty: "integer",
+            optional: false,
+            description: "Run id returned by `amp.run` (`runId` is accepted too)",
+        }],
+    },
+    CommandMeta {
+        name: "amp.result",
+        description: "Result of a background Amp CLI run, or `{ running = true, cancelling }`.",
+        params: &[Param {
+            name: "run_id",
+            ty: "integer",
+            optional: false,
+            description: "Run id returned by `amp.run` (`runId` is accepted too)",
+        }],
+    },
+    CommandMeta {
+        name: "context.get",
+        description: "Active thread id for the session (`nil` when none is active).",
+        params: &[],
+    },
+    CommandMeta {
+        name: "context.set",
+        description: "Set or clear the active thread id.",
+        params: &[Param {
+            name: "thread_id",
+            ty: "string",
+            optional: true,
+            description: "Thread id (`T-<uuid>`); omit or `nil` to clear",
+        }],
+    },
+    CommandMeta {
+        name: "threads.create",
+        description:
+            "Start a new Amp thread; its first message runs in the background (see `amp.result`).",
+        params: &[
+            Param {
+                name: "message",
+                ty: "string",
+                optional: false,
+                description: "First message of the thread",
+            },
+            Param {
+                name: "cwd",
+                ty: "string",
+                optional: true,
+                description: "Working directory for the CLI",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "threads.storage_info",
+        description:
+            "Resolved Amp thread directory, how it was found, and its file count and size.",
+        params: &[],
+    },
+    CommandMeta {
+        name: "health",
+        description: "Run diagnostic checks (database, Amp CLI) for `:checkhealth amp_extras`.",
+        params: &[],
+    },
+    CommandMeta {
+        name: "db.rebuild",
+        description: "Move a damaged prompts database aside and start a new one.",
+        params: &[],
+    },
+    CommandMeta {
+        name: "db.warmup",
+        description: "Open and migrate the prompts database ahead of first use.",
+        params: &[],
+    },
+    CommandMeta {
+        name: "format.render",
+        description: "Render a configured message template.",
+        params: &[
+            Param {
+                name: "template",
+                ty: "string",
+                optional: false,
+                description: "Template name: `selection`, `diagnostic` or `file_ref`",
+            },
+            Param {
+                name: "values",
+                ty: "table",
+                optional: true,
+                description: "Placeholder values",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "format.preview",
+        description:
+            "Preview a template against sample values without failing on invalid templates.",
+        params: &[
+            Param {
+                name: "template_name",
+                ty: "string",
+                optional: false,
+                description: "Template name: `selection`, `diagnostic` or `file_ref`",
+            },
+            Param {
+                name: "sample",
+                ty: "table",
+                optional: true,
+                description: "Placeholder values",
+            },
+            Param {
+                name: "template",
+                ty: "string",
+                optional: true,
+                description: "Template text to validate instead of the configured one",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "lint.check",
+        description: "Check outgoing text for oversize payloads and likely secrets.",
+        params: &[
+            Param {
+                name: "text",
+                ty: "string",
+                optional: false,
+                description: "Text about to be sent",
+            },
+            Param {
+                name: "allow_secrets",
+                ty: "boolean",
+                optional: true,
+                description: "Skip the secret scan for this call",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "meta.generate",
+        description: "Generate the command reference (Markdown) and Lua annotations.",
+        params: &[
+            Param {
+                name: "markdown_path",
+                ty: "string",
+                optional: true,
+                description: "Write the Markdown reference here instead of returning it",
+            },
+            Param {
+                name: "lua_path",
+                ty: "string",
+                optional: true,
+                description: "Write the Lua annotations here instead of returning them",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "plugin.reload_prepare",
+        description: "Release re-initializable state (database pool, caches) before `:AmpReload`.",
+        params: &[],
+    },
+    CommandMeta {
+        name: "prompts.list",
+        description: "List prompts, optionally one page at a time.",
+        params: &[
+            Param {
+                name: "cursor",
+                ty: "string",
+                optional: true,
+                description: "Opaque cursor from a previous page's `next_cursor`",
+            },
+            Param {
+                name: "page_size",
+                ty: "integer",
+                optional: true,
+                description: "Items per page (1-500, default 50)",
+            },
+            Param {
+                name: "sort",
+                ty: "string",
+                optional: true,
+                description: "`updated` (default), `most_used` or `recently_used`; unpaged only",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "prompts.search",
+        description: "Full-text search over prompts, best matches first, optionally paged.",
+        params: &[
+            Param {
+                name: "query",
+                ty: "string",
+                optional: false,
+                description: "Search words; the last word matches as a prefix",
+            },
+            Param {
+                name: "limit",
+                ty: "integer",
+                optional: true,
+                description: "Maximum results (default 50); unpaged only",
+            },
+            Param {
+                name: "cursor",
+                ty: "string",
+                optional: true,
+                description: "Opaque cursor from a previous page's `next_cursor`",
+            },
+            Param {
+                name: "page_size",
+                ty: "integer",
+                optional: true,
+                description: "Items per page (1-500, default 50)",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "prompts.recent",
+        description: "Most recently used prompts.",
+        params: &[Param {
+            name: "limit",
+            ty: "integer",
+            optional: true,
+            description: "Maximum results (default 10)",
+        }],
+    },
+    CommandMeta {
+        name: "prompts.create",
+        description: "Create a prompt.",
+        params: &[
+            Param {
+                name: "title",
+                ty: "string",
+                optional: false,
+                description: "Display title",
+            },
+            Param {
+                name: "description",
+                ty: "string",
+                optional: true,
+                description: "Optional description",
+            },
+            Param {
+                name: "content",
+                ty: "string",
+                optional: false,
+                description: "Prompt text",
+            },
+            Param {
+                name: "tags",
+                ty: "string[]",
+                optional: true,
+                description: "Tags",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "prompts.update",
+        description: "Replace a prompt's fields.",
+        params: &[
+            Param {
+                name: "id",
+                ty: "string",
+                optional: false,
+                description: "Prompt id",
+            },
+            Param {
+                name: "title",
+                ty: "string",
+                optional: false,
+                description: "Display title",
+            },
+            Param {
+                name: "description",
+                ty: "string",
+                optional: true,
+                description: "Optional description",
+            },
+            Param {
+                name: "content",
+                ty: "string",
+                optional: false,
+                description: "Prompt text",
+            },
+            Param {
+                name: "tags",
+                ty: "string[]",
+                optional: true,
+                description: "Tags",
+            },
+        ],
+    },
+    CommandMeta {
+        name: "prompts.delete",
+        description: "Delete a prompt.",
+        params: &[Param {
+            name: "id",
+            ty: "string",
+            optional: false,
+            description: "Prompt id",
+        }],
+    },
+    CommandMeta {
+        name: "prompts.use",
+        description: "Record a prompt use in the background.",
+        params: &[Param {
+            name: "id",
+            ty: "string",
+            optional: false,
+            description: "Prompt id",
+        }],
+    },
+    CommandMeta {
+        name: "prompts.mark_used",
+        description: "Record a prompt use and return the updated prompt.",
+        params: &[Param {
+            name: "id",
+            ty: "string",
+            optional: false,
+            description: "Prompt id",
+        }],
+    },
+    CommandMeta {
+        name: "prompts.import_from_directory",
+        description: "Import `.md`/`.txt` files from a directory as prompts.",
+        params: &[Param {
+            name: "path",
+            ty: "string",
+            optional: false,
+            description: "Directory to scan (non-recursive)",
+        }],
+    },
+    CommandMeta {
+        name: "prompts.expand_mentions",
+        description:
+            "Replace `@prompt:<title-or-id>` mentions with prompt content (`@@prompt:` escapes).",
+        params: &[Param {
+            name: "text",
+            ty: "string",
+            optional: false,
+            description: "Message to expand; nested mentions expand up to 3 levels",
+        }],
+    },
+];
+
+/// Look up metadata for a command
+pub fn get(name: &str) -> Option<&'static CommandMeta> {
+    COMMANDS.iter().find(|meta| meta.name == name)
+}
+
+/// Commands sorted by name
+fn sorted() -> Vec<&'static CommandMeta> {
+    let mut commands: Vec<_> = COMMANDS.iter().collect();
+    commands.sort_by_key(|meta| meta.name);
+    commands
+}
+
+/// Category of a command: the part before the first `.`
+fn category(name: &str) -> &str {
+    name.split_once('.')
+        .map_or("general", |(category, _)| category)
+}
+
+/// Lua class name for a command's arguments (`prompts.mark_used` -> `PromptsMarkUsed`)
+fn class_name(name: &str) -> String {
+    name.split(['.', '_'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Markdown command reference, grouped by category
+pub fn markdown() -> String {
+    let mut groups: BTreeMap<&str, Vec<&CommandMeta>> = BTreeMap::new();
+    for meta in sorted() {
+        groups.entry(category(meta.name)).or_default().push(meta);
+    }
+
+    let mut out = format!(
+        "# Commands\n\n<!-- {} -->\n\nCall from Lua with `require(\"amp_extras.ffi\").call(name, args)`.\n",
+        GENERATED_NOTE
+    );
+
+    for (category, commands) in groups {
+        out.push_str(&format!("\n## {}\n", category));
+
+        for meta in commands {
+            out.push_str(&format!("\n### `{}`\n\n{}\n", meta.name, meta.description));
+
+            if super::is_async(meta.name) {
+                out.push_str(
+                    "\nRuns in the background and returns `{ started = true, async = true }` immediately.\n",
+                );
+            }
+
+            if meta.params.is_empty() {
+                out.push_str("\nNo arguments.\n");
+                continue;
+            }
+
+            out.push_str(
+                "\n| Argument | Type | Required | Description |\n| --- | --- | --- | --- |\n",
+            );
+            for param in meta.params {
+                out.push_str(&format!(
+                    "| `{}` | `{}` | {} | {} |\n",
+                    param.name,
+                    param.ty,
+                    if param.optional { "no" } else { "yes" },
+                    param.description
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+/// lua-language-server annotations for command names and argument tables
+pub fn lua_annotations() -> String {
+    let commands = sorted();
+
+    let mut out = format!(
+        "---@meta\n-- {}\n\n---Command names accepted by `require(\"amp_extras.ffi\").call`\n---@alias AmpExtras.Command\n",
+        GENERATED_NOTE
+    );
+    for meta in &commands {
+        out.push_str(&format!("---| \"{}\"\n", meta.name));
+    }
+
+    for meta in &commands {
+        out.push_str(&format!(
+            "\n---Arguments for `{}`: {}\n---@class AmpExtras.Args.{}\n",
+            meta.name,
+            meta.description,
+            class_name(meta.name)
+        ));
+        for param in meta.params {
+            out.push_str(&format!(
+                "---@field {}{} {} {}\n",
+                param.name,
+                if param.optional { "?" } else { "" },
+                param.ty,
+                param.description
+            ));
+        }
+    }
+
+    out
+}
+
+/// Generate documentation artifacts
+///
+/// Args: `{markdown_path?, lua_path?}`
+///
+/// Each artifact is written to its path when given (the path is echoed back
+/// as `markdown_path`/`lua_path`), otherwise returned as a string
+/// (`markdown`/`lua`).
+pub fn generate(args: Value) -> Result<Value> {
+    let mut result = Map::new();
+
+    for (key, path_key, contents) in [
+        ("markdown", "markdown_path", markdown()),
+        ("lua", "lua_path", lua_annotations()),
+    ] {
+        match args.get(path_key).and_then(|v| v.as_str()) {
+            Some(path) => {
+                std::fs::write(path, contents)?;
+                result.insert(path_key.to_string(), json!(path));
+            },
+            None => {
+                result.insert(key.to_string(), json!(contents));
+            },
+        }
+    }
+
+    Ok(Value::Object(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Path relative to the repository root
+    fn repo_path(relative: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../..")
+            .join(relative)
+    }
+
+    /// Compare a checked-in artifact with freshly generated output
+    ///
+    /// With `AMP_EXTRAS_BLESS=1` the file is rewritten instead.
+    fn check_artifact(relative: &str, generated: &str) {
+        let path = repo_path(relative);
+        if std::env::var_os("AMP_EXTRAS_BLESS").is_some() {
+            std::fs::write(&path, generated).unwrap();
+            return;
+        }
+
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} is out of date; run `just gen-docs`",
+            relative
+        );
+    }
+
+    #[test]
+    fn test_every_registered_command_has_metadata() {
+        for name in super::super::list_commands() {
+            assert!(get(&name).is_some(), "missing metadata for '{}'", name);
+        }
+    }
+
+    #[test]
+    fn test_metadata_only_for_registered_commands() {
+        let registered = super::super::list_commands();
+        for meta in COMMANDS {
+            assert!(
+                registered.iter().any(|name| name == meta.name),
+                "metadata for unregistered command '{}'",
+                meta.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        assert_eq!(markdown(), markdown());
+        assert_eq!(lua_annotations(), lua_annotations());
+    }
+
+    #[test]
+    fn test_markdown_is_up_to_date() {
+        check_artifact("docs/commands.md", &markdown());
+    }
+
+    #[test]
+    fn test_lua_annotations_are_up_to_date() {
+        check_artifact("lua/amp_extras/meta/commands.lua", &lua_annotations());
+    }
+
+    #[test]
+    fn test_class_name() {
+        assert_eq!(class_name("ping"), "Ping");
+        assert_eq!(class_name("prompts.mark_used"), "PromptsMarkUsed");
+        assert_eq!(
+            class_name("prompts.import_from_directory"),
+            "PromptsImportFromDirectory"
+        );
+    }
+
+    #[test]
+    fn test_category() {
+        assert_eq!(category("prompts.list"), "prompts");
+        assert_eq!(category("ping"), "general");
+    }
+
+    #[test]
+    fn test_generate_returns_strings_without_paths() {
+        let result = generate(json!({})).unwrap();
+        assert!(result["markdown"]
+            .as_str()
+            .unwrap()
+            .starts_with("# Commands"));
+        assert!(result["lua"].as_str().unwrap().starts_with("---@meta"));
+    }
+
+    #[test]
+    fn test_generate_writes_to_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let md = dir.path().join("commands.md");
+
+        let result = generate(json!({ "markdown_path": md.to_str().unwrap() })).unwrap();
+        assert_eq!(result["markdown_path"], json!(md.to_str().unwrap()));
+        assert!(result.get("markdown").is_none());
+        assert!(result.get("lua").is_some());
+        assert_eq!(std::fs::read_to_string(&md).unwrap(), markdown());
+    }
+}
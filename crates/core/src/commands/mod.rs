@@ -10,18 +10,37 @@
 //!    Result<Value>`
 //! 2. Register in `REGISTRY`: `("category.action", my_command as
 //!    CommandHandler)`
-//! 3. Add tests for the command
+//! 3. Describe it in `meta::COMMANDS` and run `just gen-docs`
+//! 4. Add tests for the command
+//!
+//! ## Renaming a command
+//!
+//! Register the new name in `REGISTRY` and add the old one to `ALIASES`
+//! with `deprecated: true`. Calls through the old name keep working, and
+//! the first one per session queues a warning for Lua (see
+//! [`take_warnings`]).
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Mutex;
 
 use once_cell::sync::Lazy;
 use serde_json::Value;
 
 use crate::errors::{AmpError, Result};
 
+mod actions;
+mod amp;
+mod context;
+mod db;
+mod format;
+mod health;
+mod lint;
+pub mod meta;
+mod plugin;
 mod prompts;
+mod threads;
 
 // Removed command modules:
 // - account_update
@@ -50,26 +69,179 @@ static REGISTRY: Lazy<HashMap<&'static str, CommandHandler>> = Lazy::new(|| {
     // Test command
     map.insert("ping", ping as CommandHandler);
 
+    // Introspection
+    map.insert("commands.list", list as CommandHandler);
+    map.insert("actions.list", actions::list as CommandHandler);
+    map.insert("health", health::health as CommandHandler);
+
+    // Amp CLI
+    map.insert("amp.version", amp::version as CommandHandler);
+    map.insert("amp.run", amp::run as CommandHandler);
+    map.insert("amp.cancel", amp::cancel as CommandHandler);
+    map.insert("amp.result", amp::result as CommandHandler);
+
+    // Threads
+    map.insert("threads.create", threads::create as CommandHandler);
+    map.insert(
+        "threads.storage_info",
+        threads::storage_info as CommandHandler,
+    );
+    map.insert("context.get", context::get as CommandHandler);
+    map.insert("context.set", context::set as CommandHandler);
+
     // DashX Prompts
     map.insert("prompts.list", prompts::list as CommandHandler);
+    map.insert("prompts.search", prompts::search as CommandHandler);
+    map.insert("prompts.recent", prompts::recent as CommandHandler);
     map.insert("prompts.create", prompts::create as CommandHandler);
     map.insert("prompts.update", prompts::update as CommandHandler);
     map.insert("prompts.delete", prompts::delete as CommandHandler);
     map.insert("prompts.use", prompts::use_prompt as CommandHandler);
+    map.insert("prompts.mark_used", prompts::mark_used as CommandHandler);
+    map.insert(
+        "prompts.import_from_directory",
+        prompts::import_from_directory as CommandHandler,
+    );
+    map.insert(
+        "prompts.expand_mentions",
+        prompts::expand_mentions as CommandHandler,
+    );
+
+    // Message templates
+    map.insert("format.render", format::render as CommandHandler);
+    map.insert("format.preview", format::preview as CommandHandler);
+    map.insert("lint.check", lint::check as CommandHandler);
+
+    // Plugin lifecycle
+    map.insert(
+        "plugin.reload_prepare",
+        plugin::reload_prepare as CommandHandler,
+    );
+
+    // Database
+    map.insert("db.rebuild", db::rebuild as CommandHandler);
+
+    // Documentation
+    map.insert("meta.generate", meta::generate as CommandHandler);
 
     map
 });
 
 /// Static async command registry
 static ASYNC_REGISTRY: Lazy<HashMap<&'static str, AsyncCommandHandler>> = Lazy::new(|| {
-    // No async commands currently
-    HashMap::new()
+    let mut map = HashMap::new();
+
+    // Database
+    map.insert("db.warmup", db::warmup as AsyncCommandHandler);
+
+    map
 });
 
+/// Alternate name for a registered command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alias {
+    /// Canonical command name the alias resolves to
+    pub target: &'static str,
+    /// Whether calling through the alias should warn
+    pub deprecated: bool,
+}
+
+/// Alias names and the deprecation warnings they produce
+///
+/// Tests build their own table instead of touching [`ALIASES`].
+struct AliasTable {
+    /// Maps alternate command names to their canonical registration
+    aliases: HashMap<&'static str, Alias>,
+    /// Deprecated aliases already warned about this session
+    warned: Mutex<HashSet<&'static str>>,
+    /// Warnings waiting to be shown by the Lua side
+    pending: Mutex<Vec<String>>,
+}
+
+impl AliasTable {
+    fn new(aliases: HashMap<&'static str, Alias>) -> Self {
+        Self {
+            aliases,
+            warned: Mutex::default(),
+            pending: Mutex::default(),
+        }
+    }
+
+    /// Resolve an alias to its canonical command name
+    ///
+    /// Deprecated aliases queue a warning the first time they are used.
+    fn resolve<'a>(&self, command: &'a str) -> &'a str {
+        let Some((&name, alias)) = self.aliases.get_key_value(command) else {
+            return command;
+        };
+
+        if alias.deprecated {
+            let first_use = self
+                .warned
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(name);
+            if first_use {
+                self.pending
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(format!("'{}' is deprecated, use '{}'", name, alias.target));
+            }
+        }
+
+        alias.target
+    }
+
+    fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Forget which aliases were warned about and drop queued warnings
+    fn reset(&self) -> usize {
+        self.warned
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        self.take_warnings().len()
+    }
+
+    /// Alias names, sorted
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.aliases.keys().map(|&k| k.to_string()).collect();
+        names.sort();
+        names
+    }
+}
+
+/// Static alias table
+///
+/// Maps alternate command names to their canonical registration. No
+/// command has been renamed yet, so the table starts empty; see the module
+/// docs for how to add an entry.
+static ALIASES: Lazy<AliasTable> = Lazy::new(|| AliasTable::new(HashMap::new()));
+
+/// Drain warnings produced by dispatch (e.g. deprecated command names)
+///
+/// Called by the Lua FFI wrapper after each command so warnings reach
+/// `vim.notify` without failing the call. Includes schema warnings from
+/// opening the database (see `Db::take_schema_warnings`).
+pub fn take_warnings() -> Vec<String> {
+    let mut warnings = ALIASES.take_warnings();
+    warnings.extend(crate::db::Db::take_schema_warnings());
+    warnings
+}
+
+/// Forget queued warnings and which aliases were warned about
+///
+/// Returns the number of pending warnings discarded.
+pub fn reset_warnings() -> usize {
+    ALIASES.reset() + crate::db::Db::take_schema_warnings().len()
+}
+
 /// Dispatch a command by name
 ///
 /// Looks up the command in the registry and executes it with the provided
-/// arguments.
+/// arguments. Aliases are resolved to their canonical command first.
 ///
 /// # Arguments
 /// * `command` - Command name (e.g., "ping", "threads.list")
@@ -78,6 +250,13 @@ static ASYNC_REGISTRY: Lazy<HashMap<&'static str, AsyncCommandHandler>> = Lazy::
 /// # Returns
 /// Command result as JSON Value, or error if command not found
 pub fn dispatch(command: &str, args: Value) -> Result<Value> {
+    dispatch_with(&ALIASES, command, args)
+}
+
+/// Dispatch against a specific alias table
+fn dispatch_with(aliases: &AliasTable, command: &str, args: Value) -> Result<Value> {
+    let command = aliases.resolve(command);
+
     // Try sync registry first
     if let Some(handler) = REGISTRY.get(command) {
         return handler(args);
@@ -104,6 +283,45 @@ pub fn dispatch(command: &str, args: Value) -> Result<Value> {
     Err(AmpError::CommandNotFound(command.to_string()))
 }
 
+/// Largest edit distance at which `suggest_command` offers a name
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Closest registered command to a mistyped name, for "did you mean"
+///
+/// Only names within `MAX_SUGGESTION_DISTANCE` edits are suggested; ties go
+/// to the alphabetically first name.
+pub fn suggest_command(name: &str) -> Option<String> {
+    list_commands()
+        .into_iter()
+        .map(|candidate| (levenshtein(name, &candidate), candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance between two strings (by `char`)
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Whether a command is registered as fire-and-forget
+pub fn is_async(command: &str) -> bool {
+    ASYNC_REGISTRY.contains_key(command)
+}
+
 /// List all available commands
 ///
 /// Returns a sorted list of all registered command names.
@@ -117,6 +335,50 @@ pub fn list_commands() -> Vec<String> {
     commands
 }
 
+/// List all available commands including aliases
+///
+/// Returns a sorted list of canonical command names followed by alias names.
+pub fn list_commands_with_aliases() -> Vec<String> {
+    let mut commands = list_commands();
+    commands.extend(ALIASES.names());
+    commands
+}
+
+/// Command listing for Lua
+///
+/// Args: `{include_aliases?: bool}`
+///
+/// Returns `{commands = [{name, alias_of?, deprecated?}]}` sorted by name.
+fn list(args: Value) -> Result<Value> {
+    list_with(&ALIASES, args)
+}
+
+/// `commands.list` against a specific alias table
+fn list_with(aliases: &AliasTable, args: Value) -> Result<Value> {
+    let include_aliases = args
+        .get("include_aliases")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let mut entries: Vec<Value> = list_commands()
+        .into_iter()
+        .map(|name| serde_json::json!({ "name": name }))
+        .collect();
+
+    if include_aliases {
+        entries.extend(aliases.aliases.iter().map(|(name, alias)| {
+            serde_json::json!({
+                "name": name,
+                "alias_of": alias.target,
+                "deprecated": alias.deprecated,
+            })
+        }));
+        entries.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    }
+
+    Ok(serde_json::json!({ "commands": entries }))
+}
+
 // ============================================================================
 // Test Commands
 // ============================================================================
@@ -193,6 +455,119 @@ mod tests {
         assert_eq!(result.unwrap()["pong"], json!(true));
     }
 
+    // ========================================
+    // alias tests
+    // ========================================
+
+    /// Alias table for tests, independent of the global `ALIASES`
+    fn test_aliases() -> AliasTable {
+        AliasTable::new(HashMap::from([
+            (
+                "test.ping",
+                Alias {
+                    target: "ping",
+                    deprecated: false,
+                },
+            ),
+            (
+                "test.old_ping",
+                Alias {
+                    target: "ping",
+                    deprecated: true,
+                },
+            ),
+        ]))
+    }
+
+    #[test]
+    fn test_dispatch_alias_reaches_same_handler() {
+        let aliases = test_aliases();
+        let direct = dispatch("ping", json!({"n": 1})).unwrap();
+        let aliased = dispatch_with(&aliases, "test.ping", json!({"n": 1})).unwrap();
+        assert_eq!(direct, aliased);
+    }
+
+    #[test]
+    fn test_deprecated_alias_warns_once() {
+        let aliases = test_aliases();
+        dispatch_with(&aliases, "test.old_ping", json!({})).unwrap();
+        dispatch_with(&aliases, "test.old_ping", json!({})).unwrap();
+        dispatch_with(&aliases, "test.old_ping", json!({})).unwrap();
+
+        assert_eq!(
+            aliases.take_warnings(),
+            vec!["'test.old_ping' is deprecated, use 'ping'"]
+        );
+
+        dispatch_with(&aliases, "test.old_ping", json!({})).unwrap();
+        assert!(aliases.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_reset_warns_again() {
+        let aliases = test_aliases();
+        dispatch_with(&aliases, "test.old_ping", json!({})).unwrap();
+        assert_eq!(aliases.reset(), 1);
+
+        dispatch_with(&aliases, "test.old_ping", json!({})).unwrap();
+        assert_eq!(aliases.take_warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_non_deprecated_alias_does_not_warn() {
+        let aliases = test_aliases();
+        dispatch_with(&aliases, "test.ping", json!({})).unwrap();
+        assert!(aliases.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("ping", "ping"), 0);
+        assert_eq!(levenshtein("pign", "ping"), 2);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_typo_suggests_command() {
+        assert_eq!(
+            suggest_command("thread.create").as_deref(),
+            Some("threads.create")
+        );
+        assert_eq!(
+            suggest_command("prompt.list").as_deref(),
+            Some("prompts.list")
+        );
+        assert_eq!(suggest_command("pingg").as_deref(), Some("ping"));
+    }
+
+    #[test]
+    fn test_distant_names_get_no_suggestion() {
+        assert_eq!(suggest_command("completely.different"), None);
+        assert_eq!(suggest_command("threads.delete"), None);
+    }
+
+    #[test]
+    fn test_not_found_error_carries_suggestion() {
+        let err = dispatch("thread.create", json!({})).unwrap_err();
+        assert_eq!(err.data().unwrap()["did_you_mean"], "threads.create");
+        assert!(err
+            .user_message()
+            .contains("Did you mean 'threads.create'?"));
+
+        let err = dispatch("test.unknown", json!({})).unwrap_err();
+        assert!(err.data().unwrap().get("did_you_mean").is_none());
+    }
+
+    #[test]
+    fn test_unknown_command_is_not_resolved() {
+        match dispatch_with(&test_aliases(), "test.unknown", json!({})) {
+            Err(AmpError::CommandNotFound(cmd)) => assert_eq!(cmd, "test.unknown"),
+            _ => panic!("Expected CommandNotFound error"),
+        }
+    }
+
     // ========================================
     // list_commands() tests
     // ========================================
@@ -211,6 +586,51 @@ mod tests {
         assert_eq!(commands, sorted);
     }
 
+    #[test]
+    fn test_list_commands_includes_async() {
+        let commands = list_commands();
+        assert!(commands.contains(&"db.warmup".to_string()));
+    }
+
+    #[test]
+    fn test_list_commands_excludes_aliases() {
+        let commands = list_commands();
+        assert!(!commands.contains(&"test.ping".to_string()));
+    }
+
+    #[test]
+    fn test_list_commands_with_aliases() {
+        let commands = list_commands_with_aliases();
+        assert!(commands.contains(&"ping".to_string()));
+        assert!(commands.ends_with(&ALIASES.names()));
+    }
+
+    #[test]
+    fn test_alias_names_sorted() {
+        assert_eq!(test_aliases().names(), vec!["test.old_ping", "test.ping"]);
+    }
+
+    #[test]
+    fn test_commands_list_marks_aliases() {
+        let aliases = test_aliases();
+        let result = list_with(&aliases, json!({"include_aliases": true})).unwrap();
+        let entries = result["commands"].as_array().unwrap();
+
+        let old = entries
+            .iter()
+            .find(|e| e["name"] == json!("test.old_ping"))
+            .unwrap();
+        assert_eq!(old["alias_of"], json!("ping"));
+        assert_eq!(old["deprecated"], json!(true));
+
+        let plain = list_with(&aliases, json!({})).unwrap();
+        assert!(plain["commands"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|e| e.get("alias_of").is_none()));
+    }
+
     #[test]
     fn test_list_commands_not_empty() {
         let commands = list_commands();
@@ -0,0 +1,36 @@
+use serde_json::{json, Value};
+
+use crate::{cli, db::Db, errors::Result, runtime};
+
+/// State that survives `plugin.reload_prepare` and needs a Neovim restart
+///
+/// - `core_library`: Lua keeps the loaded cdylib; new Rust code needs a restart
+/// - `runtime`: the shared Tokio runtime lives for the whole process
+const REQUIRES_RESTART: &[&str] = &["core_library", "runtime"];
+
+/// Release re-initializable state before reloading the plugin (`:AmpReload`)
+///
+/// Closes the database pool (reopened lazily on next use), clears the Amp
+/// CLI version cache and drops queued warnings. Setup can run again
+/// afterwards. Returns what was released:
+///
+/// ```lua
+/// {
+///   released = { db_pool = true, amp_version_cache = false, warnings = 0 },
+///   requires_restart = { "core_library", "runtime" },
+/// }
+/// ```
+pub fn reload_prepare(_args: Value) -> Result<Value> {
+    let db_pool = runtime::block_on(Db::close());
+    let amp_version_cache = cli::clear_version_cache();
+    let warnings = super::reset_warnings();
+
+    Ok(json!({
+        "released": {
+            "db_pool": db_pool,
+            "amp_version_cache": amp_version_cache,
+            "warnings": warnings,
+        },
+        "requires_restart": REQUIRES_RESTART,
+    }))
+}
@@ -1,11 +1,85 @@
-use crate::{db::prompts, errors::Result, runtime};
+use crate::{
+    db::{
+        import,
+        paging::PageRequest,
+        prompts::{self, PromptSort},
+    },
+    errors::{AmpError, Result},
+    mentions::{self, Resolved},
+    runtime,
+};
 use serde_json::{json, Value};
 
-pub fn list(_args: Value) -> Result<Value> {
-    let prompts = runtime::block_on(async { prompts::list_prompts().await })?;
+pub fn list(args: Value) -> Result<Value> {
+    // Paged form: {cursor?, page_size?} -> {items, next_cursor?}
+    // Paging follows the default (updated) order only
+    if let Some(page) = PageRequest::from_args("prompts.list", &args)? {
+        if args.get("sort").is_some_and(|v| !v.is_null()) {
+            return Err(AmpError::InvalidArgs {
+                command: "prompts.list".to_string(),
+                reason: "sort cannot be combined with cursor/page_size".to_string(),
+            });
+        }
+        let page = runtime::block_on(async { prompts::list_prompts_page(page).await })?;
+        return Ok(json!(page));
+    }
+
+    let sort = match args.get("sort").and_then(|v| v.as_str()) {
+        Some(value) => PromptSort::parse(value).ok_or_else(|| AmpError::InvalidArgs {
+            command: "prompts.list".to_string(),
+            reason: format!(
+                "unknown sort '{}' (expected updated, most_used or recently_used)",
+                value
+            ),
+        })?,
+        None => PromptSort::default(),
+    };
+
+    let prompts = runtime::block_on(async { prompts::list_prompts_sorted(sort).await })?;
     Ok(json!({ "prompts": prompts }))
 }
 
+/// Full-text search: `{query, limit?}` -> `{prompts}`
+///
+/// Paged form: `{query, cursor?, page_size?}` -> `{items, next_cursor?}`,
+/// with the cursor keyed on FTS rank (see `prompts::search_fts_page`).
+pub fn search(args: Value) -> Result<Value> {
+    let query = args
+        .get("query")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing query")?;
+
+    if let Some(page) = PageRequest::from_args("prompts.search", &args)? {
+        if args.get("limit").is_some_and(|v| !v.is_null()) {
+            return Err(AmpError::InvalidArgs {
+                command: "prompts.search".to_string(),
+                reason: "limit cannot be combined with cursor/page_size".to_string(),
+            });
+        }
+        let page = runtime::block_on(async { prompts::search_fts_page(query, page).await })?;
+        return Ok(json!(page));
+    }
+
+    let limit = limit_arg(&args, 50);
+    let prompts = runtime::block_on(async { prompts::search_fts(query, limit).await })?;
+    Ok(json!({ "prompts": prompts }))
+}
+
+/// Recently used prompts: `{limit?}` -> `{prompts}`
+pub fn recent(args: Value) -> Result<Value> {
+    let limit = limit_arg(&args, 10);
+    let prompts = runtime::block_on(async { prompts::recent(limit).await })?;
+    Ok(json!({ "prompts": prompts }))
+}
+
+/// Read an optional `limit` argument, clamped to a sane maximum
+fn limit_arg(args: &Value, default: u32) -> u32 {
+    args.get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|n| n.clamp(1, 500) as u32)
+        .unwrap_or(default)
+}
+
 pub fn create(args: Value) -> Result<Value> {
     let title = args
         .get("title")
@@ -80,6 +154,62 @@ pub fn delete(args: Value) -> Result<Value> {
     Ok(json!({ "success": true }))
 }
 
+/// Record a use synchronously: `{id}` -> updated prompt
+///
+/// Unlike `prompts.use` this waits for the write and returns the new
+/// `usage_count`/`last_used_at`.
+pub fn mark_used(args: Value) -> Result<Value> {
+    let id = args
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing id")?;
+
+    let prompt = runtime::block_on(async { prompts::mark_used(id).await })?;
+    Ok(json!(prompt))
+}
+
+/// Import `.md`/`.txt` files from a directory: `{path}` ->
+/// `{imported, skipped, updated, failed: [{path, reason}]}`
+pub fn import_from_directory(args: Value) -> Result<Value> {
+    let path = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AmpError::InvalidArgs {
+            command: "prompts.import_from_directory".to_string(),
+            reason: "missing path".to_string(),
+        })?;
+
+    let summary = runtime::block_on(async {
+        import::import_from_directory(std::path::Path::new(path)).await
+    })?;
+    Ok(json!(summary))
+}
+
+/// Expand `@prompt:` mentions: `{text}` -> `{text, expansions, unresolved}`
+///
+/// Mentions resolve by id or title (see `prompts::find_by_reference`);
+/// content is inserted verbatim. See `crate::mentions` for escaping and
+/// nesting rules.
+pub fn expand_mentions(args: Value) -> Result<Value> {
+    let text = args
+        .get("text")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| AmpError::InvalidArgs {
+            command: "prompts.expand_mentions".to_string(),
+            reason: "text must be a string".to_string(),
+        })?;
+
+    let expanded = mentions::expand(text, |reference| {
+        let prompt = runtime::block_on(prompts::find_by_reference(reference))?;
+        Ok(prompt.map(|p| Resolved {
+            id: p.id,
+            title: p.title,
+            content: p.content,
+        }))
+    })?;
+    Ok(json!(expanded))
+}
+
 pub fn use_prompt(args: Value) -> Result<Value> {
     let id = args
         .get("id")
@@ -0,0 +1,174 @@
+use std::{path::PathBuf, time::Duration};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{json, Value};
+
+use crate::{
+    cli::{self, RunOutput, RunRequest},
+    errors::{AmpError, Result},
+    runtime,
+    threads::storage,
+};
+
+/// Thread ids as printed by the Amp CLI (`T-` followed by a UUID)
+static THREAD_ID: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\bT-[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b")
+        .expect("thread id pattern is valid")
+});
+
+/// Time limit for `amp threads new`
+///
+/// `threads.create` blocks Neovim until the thread exists, so a stalled CLI
+/// must fail fast instead of waiting out `cli::DEFAULT_RUN_TIMEOUT`.
+const THREADS_NEW_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Create a thread: `{message, cwd?}` -> `{thread_id, run_id}`
+///
+/// Runs `amp threads new` and returns once it has printed the new thread's
+/// id. `message` is then sent in the background with `amp threads continue
+/// <id> --execute` (message on stdin, so its length isn't bound by argument
+/// limits), which is a full agent turn: follow it with `amp.result` /
+/// `amp.cancel` on `run_id`. A failing `threads new` is an `AmpCliError`
+/// carrying the CLI's stderr; one still running after
+/// `THREADS_NEW_TIMEOUT` is killed and reported as an `AmpCliError` too. The new thread becomes the active thread
+/// (see `context.get`) even if the background turn later fails.
+pub fn create(args: Value) -> Result<Value> {
+    let message = args
+        .get("message")
+        .and_then(|v| v.as_str())
+        .filter(|m| !m.trim().is_empty())
+        .ok_or_else(|| AmpError::InvalidArgs {
+            command: "threads.create".to_string(),
+            reason: "message must be a non-empty string".to_string(),
+        })?;
+    let cwd = args.get("cwd").and_then(|v| v.as_str()).map(PathBuf::from);
+
+    let new = runtime::block_on(cli::run(RunRequest {
+        args: vec!["threads".into(), "new".into()],
+        stdin: None,
+        cwd: cwd.clone(),
+        timeout: Some(THREADS_NEW_TIMEOUT),
+    }))?;
+    let new = check_exit(new, "threads new")?;
+    let thread_id = parse_thread_id(&new.stdout).ok_or_else(|| {
+        AmpError::AmpCliError(format!(
+            "'amp threads new' printed no thread id: {}",
+            new.stdout.trim()
+        ))
+    })?;
+
+    super::context::set_active_thread(Some(thread_id.clone()));
+
+    let run_id = cli::start(RunRequest {
+        args: vec![
+            "threads".into(),
+            "continue".into(),
+            thread_id.clone(),
+            "--execute".into(),
+        ],
+        stdin: Some(message.to_string()),
+        cwd,
+        timeout: None,
+    })?;
+
+    Ok(json!({ "thread_id": thread_id, "run_id": run_id }))
+}
+
+/// Where thread files are read from: `{}` -> `{path, source, file_count, total_bytes}`
+///
+/// `source` is `config`, `env` or `platform_default`. Fails with a
+/// `ConfigError` listing the locations tried when no thread store is found.
+pub fn storage_info(_args: Value) -> Result<Value> {
+    Ok(json!(storage::info()?))
+}
+
+/// Whether `id` is exactly one thread id
+pub(super) fn is_thread_id(id: &str) -> bool {
+    THREAD_ID
+        .find(id)
+        .is_some_and(|m| m.start() == 0 && m.end() == id.len())
+}
+
+/// First thread id in CLI output
+fn parse_thread_id(output: &str) -> Option<String> {
+    THREAD_ID.find(output).map(|m| m.as_str().to_string())
+}
+
+/// Turn a non-zero exit into an `AmpCliError` with the CLI's stderr
+fn check_exit(output: RunOutput, step: &str) -> Result<RunOutput> {
+    if output.exit_code == Some(0) {
+        return Ok(output);
+    }
+
+    let status = match output.exit_code {
+        Some(code) => format!("exited with code {}", code),
+        None => "was terminated by a signal".to_string(),
+    };
+    let stderr = output.stderr.trim();
+    Err(AmpError::AmpCliError(if stderr.is_empty() {
+        format!("'amp {}' {}", step, status)
+    } else {
+        format!("'amp {}' {}: {}", step, status, stderr)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(exit_code: Option<i32>, stderr: &str) -> RunOutput {
+        RunOutput {
+            run_id: 0,
+            stdout: String::new(),
+            stderr: stderr.to_string(),
+            exit_code,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_thread_id() {
+        let id = "T-5928a90d-d53b-488f-a829-4e36442142ee";
+        assert_eq!(parse_thread_id(&format!("{}\n", id)).as_deref(), Some(id));
+        assert_eq!(
+            parse_thread_id(&format!(
+                "Created thread {} (https://ampcode.com/threads/{})",
+                id, id
+            ))
+            .as_deref(),
+            Some(id)
+        );
+        assert_eq!(parse_thread_id("T-not-a-thread"), None);
+        assert_eq!(parse_thread_id(""), None);
+    }
+
+    #[test]
+    fn test_is_thread_id() {
+        assert!(is_thread_id("T-5928a90d-d53b-488f-a829-4e36442142ee"));
+        assert!(!is_thread_id(" T-5928a90d-d53b-488f-a829-4e36442142ee"));
+        assert!(!is_thread_id("T-5928a90d-d53b-488f-a829-4e36442142ee/x"));
+        assert!(!is_thread_id("T-123"));
+    }
+
+    #[test]
+    fn test_check_exit_surfaces_stderr() {
+        assert!(check_exit(output(Some(0), "noise"), "threads new").is_ok());
+
+        let err = check_exit(output(Some(1), "Not logged in\n"), "threads new").unwrap_err();
+        assert_eq!(err.category(), "amp_cli");
+        assert!(err
+            .to_string()
+            .contains("exited with code 1: Not logged in"));
+
+        let err = check_exit(output(None, ""), "threads new").unwrap_err();
+        assert!(err.to_string().contains("terminated by a signal"));
+    }
+
+    #[test]
+    fn test_create_requires_message() {
+        assert!(create(json!({})).is_err());
+        assert!(create(json!({ "message": "  " })).is_err());
+        assert!(create(json!({ "message": 1 })).is_err());
+    }
+}
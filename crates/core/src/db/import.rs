@@ -0,0 +1,247 @@
+//! Bulk import of prompts from plain files
+//!
+//! Each `.md`/`.txt` file directly inside the directory becomes one prompt:
+//! the file stem is the title and the file contents are the prompt text.
+//! Imported files are recorded in `prompt_sources` by absolute path and
+//! content hash, so re-running an import:
+//!
+//! - skips files whose contents were already imported (from any path),
+//! - updates the prompt of a known path whose contents changed,
+//! - imports everything else as a new prompt.
+//!
+//! Files that can't be read as UTF-8 text are listed in the summary's
+//! `failed` and the import carries on with the next file.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::Db;
+use crate::errors::{AmpError, Result};
+
+/// File extensions picked up by the importer
+const EXTENSIONS: &[&str] = &["md", "txt"];
+
+/// Result of an import run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    pub imported: u32,
+    pub skipped: u32,
+    pub updated: u32,
+    /// Files that couldn't be read
+    pub failed: Vec<ImportFailure>,
+}
+
+/// A file left out of an import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportFailure {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Import every prompt file in `dir` (non-recursive)
+pub async fn import_from_directory(dir: &Path) -> Result<ImportSummary> {
+    let dir = tokio::fs::canonicalize(dir)
+        .await
+        .map_err(|e| AmpError::ValidationError(format!("{}: {}", dir.display(), e)))?;
+    if !dir.is_dir() {
+        return Err(AmpError::ValidationError(format!(
+            "Not a directory: {}",
+            dir.display()
+        )));
+    }
+
+    let mut summary = ImportSummary::default();
+    for path in prompt_files(&dir).await? {
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) => {
+                summary.failed.push(ImportFailure {
+                    path: path.to_string_lossy().into_owned(),
+                    reason: e.to_string(),
+                });
+                continue;
+            },
+        };
+        let Some(title) = title_from_path(&path) else {
+            continue;
+        };
+
+        match import_file(&path, &title, &content).await? {
+            Outcome::Imported => summary.imported += 1,
+            Outcome::Updated => summary.updated += 1,
+            Outcome::Skipped => summary.skipped += 1,
+        }
+    }
+
+    Ok(summary)
+}
+
+enum Outcome {
+    Imported,
+    Updated,
+    Skipped,
+}
+
+/// Import or update a single file inside one transaction
+async fn import_file(path: &Path, title: &str, content: &str) -> Result<Outcome> {
+    let pool = Db::pool().await?;
+    let path_str = path.to_string_lossy();
+    let hash = content_hash(content);
+    let now = Utc::now().timestamp();
+
+    let _guard = Db::write_guard().await;
+    let mut tx = pool.begin().await?;
+
+    let known: Option<(String, String)> =
+        sqlx::query_as("SELECT prompt_id, content_hash FROM prompt_sources WHERE path = ?")
+            .bind(path_str.as_ref())
+            .fetch_optional(&mut *tx)
+            .await?;
+
+    let outcome = match known {
+        Some((_, known_hash)) if known_hash == hash => Outcome::Skipped,
+        Some((prompt_id, _)) => {
+            sqlx::query("UPDATE prompts SET title = ?, content = ?, updated_at = ? WHERE id = ?")
+                .bind(title)
+                .bind(content)
+                .bind(now)
+                .bind(&prompt_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "UPDATE prompts_fts SET title = ?, content = ?
+                 WHERE rowid = (SELECT rowid FROM prompts WHERE id = ?)",
+            )
+            .bind(title)
+            .bind(content)
+            .bind(&prompt_id)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                "UPDATE prompt_sources SET content_hash = ?, imported_at = ? WHERE path = ?",
+            )
+            .bind(&hash)
+            .bind(now)
+            .bind(path_str.as_ref())
+            .execute(&mut *tx)
+            .await?;
+            Outcome::Updated
+        },
+        None => {
+            let duplicate: Option<i64> =
+                sqlx::query_scalar("SELECT 1 FROM prompt_sources WHERE content_hash = ? LIMIT 1")
+                    .bind(&hash)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+
+            if duplicate.is_some() {
+                Outcome::Skipped
+            } else {
+                let id = Uuid::new_v4().to_string();
+                let rowid: i64 = sqlx::query_scalar(
+                    "INSERT INTO prompts (id, title, content, usage_count, created_at, updated_at)
+                     VALUES (?, ?, ?, 0, ?, ?)
+                     RETURNING rowid",
+                )
+                .bind(&id)
+                .bind(title)
+                .bind(content)
+                .bind(now)
+                .bind(now)
+                .fetch_one(&mut *tx)
+                .await?;
+                sqlx::query("INSERT INTO prompts_fts (rowid, title, content) VALUES (?, ?, ?)")
+                    .bind(rowid)
+                    .bind(title)
+                    .bind(content)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query(
+                    "INSERT INTO prompt_sources (path, prompt_id, content_hash, imported_at)
+                     VALUES (?, ?, ?, ?)",
+                )
+                .bind(path_str.as_ref())
+                .bind(&id)
+                .bind(&hash)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+                Outcome::Imported
+            }
+        },
+    };
+
+    tx.commit().await?;
+    Ok(outcome)
+}
+
+/// Prompt files directly inside `dir`, sorted by path
+async fn prompt_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    let mut files = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if entry.file_type().await?.is_file() && is_prompt_file(&path) {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn is_prompt_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXTENSIONS.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
+
+/// Prompt title for a file: its stem, trimmed (`None` if empty)
+fn title_from_path(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let title = stem.trim();
+    (!title.is_empty()).then(|| title.to_string())
+}
+
+/// SHA-256 of the file contents, hex encoded
+fn content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_prompt_file() {
+        assert!(is_prompt_file(Path::new("/p/review.md")));
+        assert!(is_prompt_file(Path::new("/p/notes.TXT")));
+        assert!(!is_prompt_file(Path::new("/p/main.rs")));
+        assert!(!is_prompt_file(Path::new("/p/README")));
+    }
+
+    #[test]
+    fn test_title_from_path() {
+        assert_eq!(
+            title_from_path(Path::new("/p/Code Review.md")).as_deref(),
+            Some("Code Review")
+        );
+        assert_eq!(
+            title_from_path(Path::new("/p/a.b.txt")).as_deref(),
+            Some("a.b")
+        );
+        assert_eq!(title_from_path(Path::new("/p/ .md")), None);
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        assert_eq!(content_hash("x"), content_hash("x"));
+        assert_ne!(content_hash("x"), content_hash("y"));
+        assert_eq!(content_hash("").len(), 64);
+    }
+}
@@ -1,66 +1,222 @@
-use crate::errors::Result;
+use crate::errors::{AmpError, Result};
 use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{Mutex, MutexGuard};
 
+pub mod import;
+pub mod paging;
 pub mod prompts;
 #[cfg(test)]
 mod prompts_test;
 pub mod schema;
 
-static DB_POOL: OnceLock<SqlitePool> = OnceLock::new();
+/// Database path recorded by `Db::configure`
+static DB_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Lazily opened connection pool (`None` until first use or after `Db::close`)
+///
+/// Replaceable rather than a `OnceCell` so `plugin.reload_prepare` can close
+/// it and a later setup can reopen it within the same process.
+static DB_POOL: RwLock<Option<SqlitePool>> = RwLock::new(None);
+
+/// Warnings from the last schema application, until taken
+static SCHEMA_WARNINGS: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+/// Serializes pool initialization
+///
+/// Concurrent first callers wait here instead of racing to open and migrate
+/// the database.
+static INIT_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Serializes writers in-process
+///
+/// WAL allows many readers but only one writer; queueing writes here
+/// avoids SQLITE_BUSY errors when several commands write at once.
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Maximum pooled connections (WAL readers run in parallel)
+const MAX_CONNECTIONS: u32 = 8;
+
+/// Prepared statements cached per connection
+///
+/// Hot paths (autocomplete prefix lookups, search) reuse the same few
+/// statements, so this only needs to comfortably exceed the number of
+/// distinct queries in db::prompts.
+const STATEMENT_CACHE_CAPACITY: usize = 128;
 
 pub struct Db;
 
 impl Db {
-    /// Initialize the database connection pool
+    /// Record the database path without touching the filesystem
+    ///
+    /// The database is opened and migrated on first use (see `Db::pool`).
+    /// An already open pool keeps its database until `Db::close`.
+    pub fn configure(path: impl Into<PathBuf>) {
+        *DB_PATH.write().unwrap_or_else(|e| e.into_inner()) = Some(path.into());
+    }
+
+    /// Configure the database path and open it immediately
+    ///
+    /// A pool open on a different database is closed first.
     pub async fn init(path: &str) -> Result<()> {
-        if DB_POOL.get().is_some() {
-            return Ok(());
+        if Self::path().is_some_and(|current| current != Path::new(path)) {
+            Self::close().await;
+        }
+        Self::configure(path);
+        Self::pool().await?;
+        Ok(())
+    }
+
+    /// Get the global connection pool, opening it on first use
+    ///
+    /// `SqlitePool` is a cheap handle; clones share the same connections.
+    pub async fn pool() -> Result<SqlitePool> {
+        if let Some(pool) = Self::current() {
+            return Ok(pool);
+        }
+
+        let _init = INIT_LOCK.lock().await;
+        // Another caller may have opened it while we waited
+        if let Some(pool) = Self::current() {
+            return Ok(pool);
+        }
+
+        let path = Self::path().ok_or_else(|| AmpError::from("Database not configured"))?;
+        let pool = Self::open(&path).await?;
+        *DB_POOL.write().unwrap_or_else(|e| e.into_inner()) = Some(pool.clone());
+        Ok(pool)
+    }
+
+    /// Close the pool if it is open
+    ///
+    /// Waits for checked-out connections to be returned. The next call to
+    /// `Db::pool` reopens the database. Returns whether a pool was closed.
+    pub async fn close() -> bool {
+        let _init = INIT_LOCK.lock().await;
+        let pool = DB_POOL.write().unwrap_or_else(|e| e.into_inner()).take();
+
+        match pool {
+            Some(pool) => {
+                pool.close().await;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Move the database aside and open a fresh one in its place
+    ///
+    /// For databases the schema can't be applied to (see `schema::apply`).
+    /// The file and its WAL/SHM sidecars are renamed to
+    /// `<name>.broken-<unix time>` rather than deleted, so prompts can still
+    /// be recovered by hand. Works whether or not the pool ever opened.
+    /// Returns the backup path, or `None` when there was no file to move.
+    pub async fn rebuild() -> Result<Option<PathBuf>> {
+        let _init = INIT_LOCK.lock().await;
+        let path = Self::path().ok_or_else(|| AmpError::from("Database not configured"))?;
+
+        let pool = DB_POOL.write().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(pool) = pool {
+            pool.close().await;
         }
 
+        let backup = if path.exists() {
+            let mut name = path.clone().into_os_string();
+            name.push(format!(".broken-{}", chrono::Utc::now().timestamp()));
+            let backup = PathBuf::from(name);
+
+            tokio::fs::rename(&path, &backup).await?;
+            for suffix in ["-wal", "-shm"] {
+                let mut sidecar = path.clone().into_os_string();
+                sidecar.push(suffix);
+                let sidecar = PathBuf::from(sidecar);
+                if sidecar.exists() {
+                    let mut moved = backup.clone().into_os_string();
+                    moved.push(suffix);
+                    tokio::fs::rename(&sidecar, PathBuf::from(moved)).await?;
+                }
+            }
+            Some(backup)
+        } else {
+            None
+        };
+
+        let pool = Self::open(&path).await?;
+        *DB_POOL.write().unwrap_or_else(|e| e.into_inner()) = Some(pool);
+        Ok(backup)
+    }
+
+    fn current() -> Option<SqlitePool> {
+        DB_POOL.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Acquire the in-process write guard
+    ///
+    /// Hold the guard for the whole write (including transactions).
+    pub async fn write_guard() -> MutexGuard<'static, ()> {
+        WRITE_LOCK.lock().await
+    }
+
+    /// Configured database path, if any
+    pub fn path() -> Option<PathBuf> {
+        DB_PATH.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Drain warnings from applying the schema (see `schema::apply`)
+    ///
+    /// Each warning is returned once, whether through setup's response or
+    /// the per-command warning queue.
+    pub fn take_schema_warnings() -> Vec<String> {
+        std::mem::take(&mut *SCHEMA_WARNINGS.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Whether the pool has been opened and migrated
+    pub fn is_initialized() -> bool {
+        DB_POOL.read().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    /// Open the database and apply the schema
+    async fn open(path: &Path) -> Result<SqlitePool> {
         // Create directory if it doesn't exist
-        if let Some(parent) = std::path::Path::new(path).parent() {
+        if let Some(parent) = path.parent() {
             tokio::fs::create_dir_all(parent)
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to create database directory: {}", e))?;
         }
 
         let pool = SqlitePoolOptions::new()
-            .max_connections(5)
+            .max_connections(MAX_CONNECTIONS)
             .connect_with(
                 sqlx::sqlite::SqliteConnectOptions::new()
                     .filename(path)
                     .create_if_missing(true)
-                    .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal),
+                    .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+                    .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
+                    .busy_timeout(Duration::from_secs(5))
+                    .statement_cache_capacity(STATEMENT_CACHE_CAPACITY),
             )
             .await?;
 
-        // Run schema migration
-        // Split by semicolon to run multiple statements
-        for statement in schema::SCHEMA.split(';') {
-            if statement.trim().is_empty() {
-                continue;
-            }
-            sqlx::query(statement).execute(&pool).await?;
-        }
+        // Apply the schema, tolerating partially upgraded databases
+        let warnings = schema::apply(&pool).await.map_err(|e| match e {
+            AmpError::MigrationError(msg) => {
+                AmpError::MigrationError(format!("{} ({})", msg, path.display()))
+            },
+            other => other,
+        })?;
+        SCHEMA_WARNINGS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend(warnings);
 
-        // Manual migrations
-        // Attempt to add description column if it doesn't exist
-        let _ = sqlx::query("ALTER TABLE prompts ADD COLUMN description TEXT")
+        // Index rows written before the FTS table existed
+        sqlx::query(schema::FTS_BACKFILL)
             .execute(&pool)
-            .await;
-
-        DB_POOL
-            .set(pool)
-            .map_err(|_| anyhow::anyhow!("Failed to set global DB pool"))?;
-
-        Ok(())
-    }
+            .await
+            .map_err(|e| AmpError::MigrationError(format!("{} ({})", e, path.display())))?;
 
-    /// Get a reference to the global connection pool
-    pub fn pool() -> Result<&'static SqlitePool> {
-        DB_POOL
-            .get()
-            .ok_or_else(|| anyhow::anyhow!("Database not initialized").into())
+        Ok(pool)
     }
 }
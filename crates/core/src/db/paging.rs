@@ -0,0 +1,180 @@
+//! Cursor-based paging for list-style commands
+//!
+//! List commands accept `{cursor?: string, page_size?: number}` and return
+//! `{items, next_cursor?}`. The cursor is an opaque base64 token encoding the
+//! sort key of the last item on the previous page, so iteration is keyset
+//! based rather than offset based:
+//!
+//! - Items inserted or re-sorted ahead of the cursor between pages are never
+//!   returned twice (no duplicates).
+//! - Such items may be missed by an iteration that is already past them; the
+//!   caller can restart from the first page to pick them up.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::errors::{AmpError, Result};
+
+/// Default number of items per page
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+
+/// Upper bound on `page_size` to keep responses reasonably sized
+pub const MAX_PAGE_SIZE: u32 = 500;
+
+/// Cursor format version, bumped if the encoded sort key changes
+const CURSOR_VERSION: &str = "v1";
+
+/// Decoded paging cursor
+///
+/// `sort_key` is the primary ordering column of the last item seen (e.g.
+/// `updated_at`), `tiebreak` disambiguates items with equal sort keys (e.g.
+/// `rowid`). Float keys such as an FTS rank are stored as their bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub sort_key: i64,
+    pub tiebreak: i64,
+}
+
+impl Cursor {
+    /// Encode the cursor as an opaque token
+    pub fn encode(&self) -> String {
+        let raw = format!("{}:{}:{}", CURSOR_VERSION, self.sort_key, self.tiebreak);
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Decode a token produced by [`Cursor::encode`]
+    pub fn decode(token: &str) -> Result<Self> {
+        let invalid = || AmpError::ValidationError(format!("Invalid cursor: {}", token));
+
+        let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let raw = String::from_utf8(bytes).map_err(|_| invalid())?;
+
+        let mut parts = raw.split(':');
+        if parts.next() != Some(CURSOR_VERSION) {
+            return Err(invalid());
+        }
+        let sort_key = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)?;
+        let tiebreak = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(invalid)?;
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(Self { sort_key, tiebreak })
+    }
+}
+
+/// Paging arguments parsed from a command's JSON args
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    pub cursor: Option<Cursor>,
+    pub page_size: u32,
+}
+
+impl PageRequest {
+    /// Parse `{cursor?, page_size?}` from command args
+    ///
+    /// Returns `None` when neither field is present so commands can keep
+    /// their unpaged response shape for existing callers.
+    pub fn from_args(command: &str, args: &Value) -> Result<Option<Self>> {
+        let cursor = args.get("cursor").filter(|v| !v.is_null());
+        let page_size = args.get("page_size").filter(|v| !v.is_null());
+
+        if cursor.is_none() && page_size.is_none() {
+            return Ok(None);
+        }
+
+        let cursor = match cursor {
+            Some(v) => {
+                let token = v.as_str().ok_or_else(|| AmpError::InvalidArgs {
+                    command: command.to_string(),
+                    reason: "cursor must be a string".to_string(),
+                })?;
+                Some(Cursor::decode(token)?)
+            },
+            None => None,
+        };
+
+        let page_size = match page_size {
+            Some(v) => v
+                .as_u64()
+                .filter(|n| (1..=MAX_PAGE_SIZE as u64).contains(n))
+                .ok_or_else(|| AmpError::InvalidArgs {
+                    command: command.to_string(),
+                    reason: format!("page_size must be between 1 and {}", MAX_PAGE_SIZE),
+                })? as u32,
+            None => DEFAULT_PAGE_SIZE,
+        };
+
+        Ok(Some(Self { cursor, page_size }))
+    }
+}
+
+/// A single page of results
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            sort_key: 1_700_000_000,
+            tiebreak: 42,
+        };
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_cursor_round_trip_negative() {
+        let cursor = Cursor {
+            sort_key: -5,
+            tiebreak: 0,
+        };
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_cursor_rejects_garbage() {
+        assert!(Cursor::decode("not a cursor!").is_err());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode("v1:abc:1")).is_err());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode("v0:1:1")).is_err());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode("v1:1:1:1")).is_err());
+    }
+
+    #[test]
+    fn test_page_request_absent() {
+        assert_eq!(PageRequest::from_args("x", &json!({})).unwrap(), None);
+        assert_eq!(PageRequest::from_args("x", &json!(null)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_page_request_defaults() {
+        let req = PageRequest::from_args("x", &json!({"cursor": null, "page_size": 10}))
+            .unwrap()
+            .unwrap();
+        assert_eq!(req.cursor, None);
+        assert_eq!(req.page_size, 10);
+    }
+
+    #[test]
+    fn test_page_request_invalid_page_size() {
+        assert!(PageRequest::from_args("x", &json!({"page_size": 0})).is_err());
+        assert!(PageRequest::from_args("x", &json!({"page_size": MAX_PAGE_SIZE + 1})).is_err());
+        assert!(PageRequest::from_args("x", &json!({"page_size": "10"})).is_err());
+    }
+}
@@ -1,5 +1,8 @@
-use super::Db;
-use crate::errors::Result;
+use super::{
+    paging::{Cursor, Page, PageRequest},
+    Db,
+};
+use crate::errors::{AmpError, Result};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -18,30 +21,139 @@ pub struct Prompt {
     pub updated_at: i64,
 }
 
+/// Prompt row with its rowid, used as the paging tiebreak
+#[derive(FromRow)]
+struct PagedPrompt {
+    row_id: i64,
+    #[sqlx(flatten)]
+    prompt: Prompt,
+}
+
+/// Search hit with its FTS rank and rowid, used as the paging keys
+#[derive(FromRow)]
+struct RankedPrompt {
+    row_id: i64,
+    fts_rank: f64,
+    #[sqlx(flatten)]
+    prompt: Prompt,
+}
+
+/// Sort order for prompt listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PromptSort {
+    /// Most recently edited first
+    #[default]
+    Updated,
+    /// Highest `usage_count` first
+    MostUsed,
+    /// Most recent `last_used_at` first; never-used prompts last
+    RecentlyUsed,
+}
+
+impl PromptSort {
+    /// Parse the `sort` argument of `prompts.list`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "updated" => Some(Self::Updated),
+            "most_used" => Some(Self::MostUsed),
+            "recently_used" => Some(Self::RecentlyUsed),
+            _ => None,
+        }
+    }
+
+    /// `ORDER BY` clause (without the keywords) for this sort
+    fn order_by(self) -> &'static str {
+        match self {
+            Self::Updated => "updated_at DESC, rowid DESC",
+            Self::MostUsed => "usage_count DESC, last_used_at DESC, rowid DESC",
+            Self::RecentlyUsed => "last_used_at DESC, usage_count DESC, rowid DESC",
+        }
+    }
+}
+
 pub async fn list_prompts() -> Result<Vec<Prompt>> {
-    let pool = Db::pool()?;
-    let prompts = sqlx::query_as::<_, Prompt>("SELECT * FROM prompts ORDER BY updated_at DESC")
-        .fetch_all(pool)
-        .await?;
+    list_prompts_sorted(PromptSort::Updated).await
+}
+
+pub async fn list_prompts_sorted(sort: PromptSort) -> Result<Vec<Prompt>> {
+    let pool = Db::pool().await?;
+    let query = format!("SELECT * FROM prompts ORDER BY {}", sort.order_by());
+    let prompts = sqlx::query_as::<_, Prompt>(&query).fetch_all(&pool).await?;
 
     Ok(prompts)
 }
 
+/// List one page of prompts in `list_prompts` order
+///
+/// The cursor encodes `(updated_at, rowid)` of the last item returned.
+pub async fn list_prompts_page(page: PageRequest) -> Result<Page<Prompt>> {
+    let pool = Db::pool().await?;
+    // Fetch one extra row to detect whether another page follows
+    let limit = i64::from(page.page_size) + 1;
+
+    let mut rows = match page.cursor {
+        Some(cursor) => {
+            sqlx::query_as::<_, PagedPrompt>(
+                "SELECT rowid AS row_id, * FROM prompts
+                 WHERE updated_at < ? OR (updated_at = ? AND rowid < ?)
+                 ORDER BY updated_at DESC, rowid DESC
+                 LIMIT ?",
+            )
+            .bind(cursor.sort_key)
+            .bind(cursor.sort_key)
+            .bind(cursor.tiebreak)
+            .bind(limit)
+            .fetch_all(&pool)
+            .await?
+        },
+        None => {
+            sqlx::query_as::<_, PagedPrompt>(
+                "SELECT rowid AS row_id, * FROM prompts
+                 ORDER BY updated_at DESC, rowid DESC
+                 LIMIT ?",
+            )
+            .bind(limit)
+            .fetch_all(&pool)
+            .await?
+        },
+    };
+
+    let has_more = rows.len() > page.page_size as usize;
+    rows.truncate(page.page_size as usize);
+
+    let next_cursor = rows.last().filter(|_| has_more).map(|last| {
+        Cursor {
+            sort_key: last.prompt.updated_at,
+            tiebreak: last.row_id,
+        }
+        .encode()
+    });
+
+    Ok(Page {
+        items: rows.into_iter().map(|row| row.prompt).collect(),
+        next_cursor,
+    })
+}
+
 pub async fn create_prompt(
     title: String,
     description: Option<String>,
     content: String,
     tags: Option<Vec<String>>,
 ) -> Result<Prompt> {
-    let pool = Db::pool()?;
+    let pool = Db::pool().await?;
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().timestamp();
 
     let tags_json = tags.map(|t| serde_json::to_string(&t).unwrap_or_default());
 
-    sqlx::query(
+    let _guard = Db::write_guard().await;
+    let mut tx = pool.begin().await?;
+
+    let rowid: i64 = sqlx::query_scalar(
         "INSERT INTO prompts (id, title, description, content, tags, usage_count, created_at, updated_at)
-         VALUES (?, ?, ?, ?, ?, 0, ?, ?)"
+         VALUES (?, ?, ?, ?, ?, 0, ?, ?)
+         RETURNING rowid"
     )
     .bind(&id)
     .bind(&title)
@@ -50,9 +162,22 @@ pub async fn create_prompt(
     .bind(&tags_json)
     .bind(now)
     .bind(now)
-    .execute(pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    sqlx::query(
+        "INSERT INTO prompts_fts (rowid, title, description, content, tags) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(rowid)
+    .bind(&title)
+    .bind(&description)
+    .bind(&content)
+    .bind(&tags_json)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
     Ok(Prompt {
         id,
         title,
@@ -73,43 +198,293 @@ pub async fn update_prompt(
     content: String,
     tags: Option<Vec<String>>,
 ) -> Result<()> {
-    let pool = Db::pool()?;
+    let pool = Db::pool().await?;
     let now = Utc::now().timestamp();
     let tags_json = tags.map(|t| serde_json::to_string(&t).unwrap_or_default());
 
+    let _guard = Db::write_guard().await;
+    let mut tx = pool.begin().await?;
+
     sqlx::query(
         "UPDATE prompts SET title = ?, description = ?, content = ?, tags = ?, updated_at = ? WHERE id = ?"
     )
-    .bind(title)
-    .bind(description)
-    .bind(content)
-    .bind(tags_json)
+    .bind(&title)
+    .bind(&description)
+    .bind(&content)
+    .bind(&tags_json)
     .bind(now)
-    .bind(id)
-    .execute(pool)
+    .bind(&id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE prompts_fts SET title = ?, description = ?, content = ?, tags = ?
+         WHERE rowid = (SELECT rowid FROM prompts WHERE id = ?)",
+    )
+    .bind(&title)
+    .bind(&description)
+    .bind(&content)
+    .bind(&tags_json)
+    .bind(&id)
+    .execute(&mut *tx)
     .await?;
 
+    tx.commit().await?;
+
     Ok(())
 }
 
 pub async fn delete_prompt(id: String) -> Result<()> {
-    let pool = Db::pool()?;
+    let pool = Db::pool().await?;
+
+    let _guard = Db::write_guard().await;
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM prompts_fts WHERE rowid = (SELECT rowid FROM prompts WHERE id = ?)")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM prompt_sources WHERE prompt_id = ?")
+        .bind(&id)
+        .execute(&mut *tx)
+        .await?;
     sqlx::query("DELETE FROM prompts WHERE id = ?")
-        .bind(id)
-        .execute(pool)
+        .bind(&id)
+        .execute(&mut *tx)
         .await?;
+
+    tx.commit().await?;
     Ok(())
 }
 
-pub async fn record_usage(id: String) -> Result<()> {
-    let pool = Db::pool()?;
+/// Count one use of a prompt and return its updated row
+///
+/// A single `UPDATE ... RETURNING *` under the write guard, so the row
+/// returned is exactly the one the use was recorded on. `None` when no
+/// prompt has this id.
+async fn bump_usage(id: &str) -> Result<Option<Prompt>> {
+    let pool = Db::pool().await?;
     let now = Utc::now().timestamp();
 
-    sqlx::query("UPDATE prompts SET usage_count = usage_count + 1, last_used_at = ? WHERE id = ?")
-        .bind(now)
-        .bind(id)
-        .execute(pool)
-        .await?;
+    let _guard = Db::write_guard().await;
+    let prompt = sqlx::query_as::<_, Prompt>(
+        "UPDATE prompts SET usage_count = usage_count + 1, last_used_at = ?
+         WHERE id = ?
+         RETURNING *",
+    )
+    .bind(now)
+    .bind(id)
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(prompt)
+}
 
+pub async fn record_usage(id: String) -> Result<()> {
+    bump_usage(&id).await?;
     Ok(())
 }
+
+/// Record a use (see `record_usage`) and return the updated prompt
+pub async fn mark_used(id: &str) -> Result<Prompt> {
+    bump_usage(id)
+        .await?
+        .ok_or_else(|| AmpError::ValidationError(format!("Prompt not found: {}", id)))
+}
+
+/// Prompts whose title starts with `prefix` (case-insensitive)
+///
+/// Used by autocomplete on every keystroke; served by `idx_prompts_title`.
+/// Most-used prompts come first.
+pub async fn find_by_title_prefix(prefix: &str, limit: u32) -> Result<Vec<Prompt>> {
+    let pool = Db::pool().await?;
+    let (lower, upper) = prefix_range(prefix);
+
+    // A range scan rather than LIKE so the bound-parameter statement stays
+    // cacheable and can use the NOCASE index
+    let prompts = sqlx::query_as::<_, Prompt>(
+        "SELECT * FROM prompts
+         WHERE title >= ? COLLATE NOCASE AND title < ? COLLATE NOCASE
+         ORDER BY usage_count DESC, updated_at DESC
+         LIMIT ?",
+    )
+    .bind(lower)
+    .bind(upper)
+    .bind(i64::from(limit))
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(prompts)
+}
+
+/// Prompt referenced by id or title, as in `@prompt:` mentions
+///
+/// Matches an exact id first, then a case-insensitive title, then a title
+/// with spaces written as hyphens (`code-review` for "Code Review"). Ties go
+/// to the most-used prompt.
+pub async fn find_by_reference(reference: &str) -> Result<Option<Prompt>> {
+    let pool = Db::pool().await?;
+
+    let prompt = sqlx::query_as::<_, Prompt>(
+        "SELECT * FROM prompts
+         WHERE id = ?1
+            OR title = ?1 COLLATE NOCASE
+            OR lower(replace(title, ' ', '-')) = lower(?1)
+         ORDER BY id = ?1 DESC, title = ?1 COLLATE NOCASE DESC, usage_count DESC
+         LIMIT 1",
+    )
+    .bind(reference)
+    .fetch_optional(&pool)
+    .await?;
+
+    Ok(prompt)
+}
+
+/// Full-text search over title, description, content and tags
+///
+/// Each whitespace-separated word of `query` must match; the last word
+/// matches as a prefix so results update while typing. Best matches first.
+pub async fn search_fts(query: &str, limit: u32) -> Result<Vec<Prompt>> {
+    let Some(match_expr) = fts_match_expr(query) else {
+        return Ok(vec![]);
+    };
+    let pool = Db::pool().await?;
+
+    let prompts = sqlx::query_as::<_, Prompt>(
+        "SELECT p.* FROM prompts_fts
+         JOIN prompts p ON p.rowid = prompts_fts.rowid
+         WHERE prompts_fts MATCH ?
+         ORDER BY prompts_fts.rank
+         LIMIT ?",
+    )
+    .bind(match_expr)
+    .bind(i64::from(limit))
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(prompts)
+}
+
+/// One page of `search_fts` results
+///
+/// Ordered by FTS rank, then rowid. The cursor encodes `(rank, rowid)` of
+/// the last hit; the rank is a float, so its bits are stored in the sort
+/// key. Ranks depend on the whole index, so edits between pages can move
+/// hits past the cursor (see `paging`).
+pub async fn search_fts_page(query: &str, page: PageRequest) -> Result<Page<Prompt>> {
+    let Some(match_expr) = fts_match_expr(query) else {
+        return Ok(Page {
+            items: vec![],
+            next_cursor: None,
+        });
+    };
+    let pool = Db::pool().await?;
+    // Fetch one extra row to detect whether another page follows
+    let limit = i64::from(page.page_size) + 1;
+
+    // The first page starts before every rank
+    let (after_rank, after_rowid) = match page.cursor {
+        Some(cursor) => (f64::from_bits(cursor.sort_key as u64), cursor.tiebreak),
+        None => (f64::NEG_INFINITY, i64::MIN),
+    };
+    let mut rows = sqlx::query_as::<_, RankedPrompt>(
+        "WITH hits AS (
+             SELECT p.rowid AS row_id, prompts_fts.rank AS fts_rank, p.*
+             FROM prompts_fts
+             JOIN prompts p ON p.rowid = prompts_fts.rowid
+             WHERE prompts_fts MATCH ?
+         )
+         SELECT * FROM hits
+         WHERE fts_rank > ? OR (fts_rank = ? AND row_id > ?)
+         ORDER BY fts_rank, row_id
+         LIMIT ?",
+    )
+    .bind(match_expr)
+    .bind(after_rank)
+    .bind(after_rank)
+    .bind(after_rowid)
+    .bind(limit)
+    .fetch_all(&pool)
+    .await?;
+
+    let has_more = rows.len() > page.page_size as usize;
+    rows.truncate(page.page_size as usize);
+
+    let next_cursor = rows.last().filter(|_| has_more).map(|last| {
+        Cursor {
+            sort_key: last.fts_rank.to_bits() as i64,
+            tiebreak: last.row_id,
+        }
+        .encode()
+    });
+
+    Ok(Page {
+        items: rows.into_iter().map(|row| row.prompt).collect(),
+        next_cursor,
+    })
+}
+
+/// Most recently used prompts (`PromptSort::RecentlyUsed` order), leaving
+/// out prompts that were never used
+pub async fn recent(limit: u32) -> Result<Vec<Prompt>> {
+    let pool = Db::pool().await?;
+
+    let query = format!(
+        "SELECT * FROM prompts WHERE last_used_at IS NOT NULL ORDER BY {} LIMIT ?",
+        PromptSort::RecentlyUsed.order_by()
+    );
+    let prompts = sqlx::query_as::<_, Prompt>(&query)
+        .bind(i64::from(limit))
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(prompts)
+}
+
+/// Half-open `[lower, upper)` range covering every string starting with
+/// `prefix`
+fn prefix_range(prefix: &str) -> (String, String) {
+    (prefix.to_string(), format!("{}{}", prefix, char::MAX))
+}
+
+/// Build an FTS5 MATCH expression from free-form user input
+///
+/// Every word is quoted so FTS5 operators and punctuation in the input are
+/// treated as text; the last word gets a prefix `*`. Words without any
+/// alphanumeric character would tokenize to nothing and are dropped.
+/// Returns `None` when no words remain.
+fn fts_match_expr(query: &str) -> Option<String> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .filter(|w| w.chars().any(char::is_alphanumeric))
+        .map(|w| format!("\"{}\"", w.replace('"', "\"\"")))
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", words.join(" ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_range() {
+        let (lower, upper) = prefix_range("Code");
+        assert_eq!(lower, "Code");
+        assert!("Code Review" >= lower.as_str() && "Code Review" < upper.as_str());
+        assert!("Codf" >= upper.as_str());
+    }
+
+    #[test]
+    fn test_fts_match_expr() {
+        assert_eq!(fts_match_expr("  "), None);
+        assert_eq!(fts_match_expr("( -- )"), None);
+        assert_eq!(fts_match_expr("code rev"), Some("\"code\" \"rev\"*".into()));
+        assert_eq!(
+            fts_match_expr("say \"hi\" OR ("),
+            Some("\"say\" \"\"\"hi\"\"\" \"OR\"*".into())
+        );
+    }
+}
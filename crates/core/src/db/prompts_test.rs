@@ -1,14 +1,46 @@
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+    use std::time::{Duration, Instant};
+
+    use once_cell::sync::Lazy;
+    use tokio::sync::{Mutex, MutexGuard};
+
+    use crate::db::import::{import_from_directory, ImportSummary};
+    use crate::db::paging::PageRequest;
     use crate::db::prompts::{
-        create_prompt, delete_prompt, list_prompts, record_usage, update_prompt,
+        create_prompt, delete_prompt, find_by_reference, find_by_title_prefix, list_prompts,
+        list_prompts_page, list_prompts_sorted, mark_used, recent, record_usage, search_fts,
+        search_fts_page, update_prompt, PromptSort,
     };
     use crate::db::Db;
     use crate::errors::Result;
-    use tempfile::tempdir;
+    use tempfile::{tempdir, TempDir};
+
+    /// Serializes tests that use the global database pool
+    static DB_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+    /// A fresh database for one test; removed when dropped
+    struct TestDb {
+        _dir: TempDir,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    /// Take the test lock and point the global pool at a new temp database
+    async fn setup_db() -> Result<TestDb> {
+        let lock = DB_LOCK.lock().await;
+        let dir = tempdir()?;
+        Db::init(dir.path().join("test_prompts.db").to_str().unwrap()).await?;
+        Ok(TestDb {
+            _dir: dir,
+            _lock: lock,
+        })
+    }
 
     #[tokio::test]
     async fn test_crud_operations() -> Result<()> {
+        let _lock = DB_LOCK.lock().await;
+
         // Setup isolated DB
         let dir = tempdir().unwrap();
         let db_path = dir.path().join("test_prompts.db");
@@ -61,4 +93,409 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_paging_covers_all_rows_without_duplicates() -> Result<()> {
+        let _guard = setup_db().await?;
+
+        // 1000 rows created within the same few seconds, so most share an
+        // updated_at and paging has to rely on the rowid tiebreak
+        let mut fixture = HashSet::new();
+        for i in 0..1000 {
+            let prompt =
+                create_prompt(format!("Paging {}", i), None, "content".into(), None).await?;
+            fixture.insert(prompt.id);
+        }
+
+        let mut seen = HashSet::new();
+        let mut cursor = None;
+        loop {
+            let page = list_prompts_page(PageRequest {
+                cursor,
+                page_size: 50,
+            })
+            .await?;
+            assert!(page.items.len() <= 50);
+
+            for item in &page.items {
+                assert!(seen.insert(item.id.clone()), "duplicate item {}", item.id);
+            }
+
+            match page.next_cursor {
+                Some(token) => cursor = Some(crate::db::paging::Cursor::decode(&token)?),
+                None => break,
+            }
+        }
+
+        assert!(fixture.is_subset(&seen), "paging missed fixture rows");
+
+        for id in fixture {
+            delete_prompt(id).await?;
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_paging_covers_all_hits_without_duplicates() -> Result<()> {
+        let _guard = setup_db().await?;
+
+        // Three content shapes, so hits share ranks in groups and paging has
+        // to rely on the rowid tiebreak within each group
+        let mut fixture = HashSet::new();
+        for i in 0..120 {
+            let content = match i % 3 {
+                0 => "needle".to_string(),
+                1 => "needle in a haystack of other words".to_string(),
+                _ => "needle needle needle".to_string(),
+            };
+            let prompt = create_prompt(format!("Search {}", i), None, content, None).await?;
+            fixture.insert(prompt.id);
+        }
+        create_prompt("Unrelated".into(), None, "haystack only".into(), None).await?;
+
+        let mut seen = HashSet::new();
+        let mut cursor = None;
+        loop {
+            let page = search_fts_page(
+                "needle",
+                PageRequest {
+                    cursor,
+                    page_size: 7,
+                },
+            )
+            .await?;
+            assert!(page.items.len() <= 7);
+
+            for item in &page.items {
+                assert!(seen.insert(item.id.clone()), "duplicate item {}", item.id);
+            }
+
+            match page.next_cursor {
+                Some(token) => cursor = Some(crate::db::paging::Cursor::decode(&token)?),
+                None => break,
+            }
+        }
+
+        assert_eq!(seen, fixture);
+
+        let empty = search_fts_page(
+            "  ",
+            PageRequest {
+                cursor: None,
+                page_size: 7,
+            },
+        )
+        .await?;
+        assert!(empty.items.is_empty() && empty.next_cursor.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_and_prefix_lookup() -> Result<()> {
+        let _guard = setup_db().await?;
+
+        let review = create_prompt(
+            "Zebra Review".into(),
+            Some("Careful code review".into()),
+            "Review this diff for subtle bugs".into(),
+            Some(vec!["zebra-tag".into()]),
+        )
+        .await?;
+        let other = create_prompt(
+            "Zebra Summary".into(),
+            None,
+            "Summarize the module".into(),
+            None,
+        )
+        .await?;
+
+        // Prefix lookup is case-insensitive and literal
+        let found = find_by_title_prefix("zebra r", 10).await?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, review.id);
+        assert!(find_by_title_prefix("zebra%", 10).await?.is_empty());
+
+        // FTS matches content words, with the last word as a prefix
+        let found = search_fts("subtle bu", 10).await?;
+        assert!(found.iter().any(|p| p.id == review.id));
+        assert!(found.iter().all(|p| p.id != other.id));
+
+        // FTS follows updates and deletes
+        update_prompt(
+            other.id.clone(),
+            "Zebra Summary".into(),
+            None,
+            "Find subtle bugs too".into(),
+            None,
+        )
+        .await?;
+        assert!(search_fts("subtle", 10)
+            .await?
+            .iter()
+            .any(|p| p.id == other.id));
+
+        delete_prompt(review.id.clone()).await?;
+        assert!(search_fts("subtle", 10)
+            .await?
+            .iter()
+            .all(|p| p.id != review.id));
+
+        // Operators in user input are treated as text
+        assert!(search_fts("\"unbalanced OR (", 10).await.is_ok());
+
+        // Recently used
+        record_usage(other.id.clone()).await?;
+        assert_eq!(recent(1).await?[0].id, other.id);
+
+        delete_prompt(other.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prefix_lookup_is_fast_on_10k_rows() -> Result<()> {
+        let _guard = setup_db().await?;
+        let pool = Db::pool().await?;
+
+        // Bulk insert in one transaction; create_prompt commits per row
+        let mut tx = pool.begin().await?;
+        for i in 0..10_000 {
+            let rowid: i64 = sqlx::query_scalar(
+                "INSERT INTO prompts (id, title, content, usage_count, created_at, updated_at)
+                 VALUES (?, ?, 'bench', 0, 0, 0) RETURNING rowid",
+            )
+            .bind(format!("bench-{}", i))
+            .bind(format!("Bench {:05}", i))
+            .fetch_one(&mut *tx)
+            .await?;
+            sqlx::query("INSERT INTO prompts_fts (rowid, title, content) VALUES (?, ?, 'bench')")
+                .bind(rowid)
+                .bind(format!("Bench {:05}", i))
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        // Warm up the statement cache
+        find_by_title_prefix("bench 1", 20).await?;
+
+        let runs = 20;
+        let start = Instant::now();
+        for i in 0..runs {
+            let found = find_by_title_prefix(&format!("bench {:02}", i), 20).await?;
+            assert_eq!(found.len(), 20);
+        }
+        let average = start.elapsed() / runs;
+
+        sqlx::query(
+            "DELETE FROM prompts_fts WHERE rowid IN (SELECT rowid FROM prompts WHERE id LIKE 'bench-%')",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query("DELETE FROM prompts WHERE id LIKE 'bench-%'")
+            .execute(&pool)
+            .await?;
+
+        // Typically well under a millisecond; generous for slow CI machines
+        assert!(
+            average < Duration::from_millis(25),
+            "prefix lookup took {:?} on average",
+            average
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_usage_sorting() -> Result<()> {
+        let _guard = setup_db().await?;
+
+        let rare = create_prompt("Rare".into(), None, "rare".into(), None).await?;
+        let popular = create_prompt("Popular".into(), None, "popular".into(), None).await?;
+        let unused = create_prompt("Unused".into(), None, "unused".into(), None).await?;
+
+        for _ in 0..3 {
+            mark_used(&popular.id).await?;
+        }
+        let marked = mark_used(&rare.id).await?;
+        assert_eq!(marked.usage_count, 1);
+        assert!(marked.last_used_at.is_some());
+
+        let ids = |prompts: Vec<crate::db::prompts::Prompt>| -> Vec<String> {
+            prompts
+                .into_iter()
+                .map(|p| p.id)
+                .filter(|id| [&rare.id, &popular.id, &unused.id].contains(&id))
+                .collect()
+        };
+
+        let most_used = ids(list_prompts_sorted(PromptSort::MostUsed).await?);
+        assert_eq!(
+            most_used,
+            vec![popular.id.clone(), rare.id.clone(), unused.id.clone()]
+        );
+
+        // Same-second timestamps fall back to usage count, so only check that
+        // used prompts come before the never-used one
+        let recently_used = ids(list_prompts_sorted(PromptSort::RecentlyUsed).await?);
+        assert_eq!(recently_used.last(), Some(&unused.id));
+
+        assert!(mark_used("missing-id").await.is_err());
+
+        for id in [rare.id, popular.id, unused.id] {
+            delete_prompt(id).await?;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_from_directory() -> Result<()> {
+        let _guard = setup_db().await?;
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("Import Review.md"), "review body")?;
+        std::fs::write(dir.path().join("Import Notes.txt"), "notes body")?;
+        std::fs::write(dir.path().join("ignored.rs"), "fn main() {}")?;
+
+        let summary = import_from_directory(dir.path()).await?;
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 2,
+                skipped: 0,
+                updated: 0,
+                failed: vec![],
+            }
+        );
+
+        // Unchanged files and copies of imported content are skipped
+        std::fs::write(dir.path().join("Import Copy.md"), "notes body")?;
+        let summary = import_from_directory(dir.path()).await?;
+        assert_eq!((summary.imported, summary.skipped), (0, 3));
+
+        // Changed files update their existing prompt
+        std::fs::write(dir.path().join("Import Review.md"), "new review body")?;
+        let summary = import_from_directory(dir.path()).await?;
+        assert_eq!((summary.updated, summary.skipped), (1, 2));
+
+        let imported: Vec<_> = list_prompts()
+            .await?
+            .into_iter()
+            .filter(|p| p.title.starts_with("Import "))
+            .collect();
+        assert_eq!(imported.len(), 2);
+        let review = imported
+            .iter()
+            .find(|p| p.title == "Import Review")
+            .unwrap();
+        assert_eq!(review.content, "new review body");
+
+        // Deleting a prompt forgets its source so it can be imported again
+        for prompt in imported {
+            delete_prompt(prompt.id).await?;
+        }
+        let summary = import_from_directory(dir.path()).await?;
+        assert_eq!(summary.imported, 2);
+
+        for prompt in list_prompts().await? {
+            if prompt.title.starts_with("Import ") {
+                delete_prompt(prompt.id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_continues_past_unreadable_files() -> Result<()> {
+        let _guard = setup_db().await?;
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("Unreadable A.md"), "first body")?;
+        std::fs::write(dir.path().join("Unreadable B.md"), [0xff, 0xfe, 0x00])?;
+        std::fs::write(dir.path().join("Unreadable C.md"), "third body")?;
+
+        let summary = import_from_directory(dir.path()).await?;
+        assert_eq!(summary.imported, 2);
+        assert_eq!(summary.failed.len(), 1);
+        assert!(summary.failed[0].path.ends_with("Unreadable B.md"));
+        assert!(!summary.failed[0].reason.is_empty());
+
+        for prompt in list_prompts().await? {
+            if prompt.title.starts_with("Unreadable ") {
+                delete_prompt(prompt.id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_close_and_reopen() -> Result<()> {
+        let _guard = setup_db().await?;
+        let prompt = create_prompt("Reopen".into(), None, "reopen".into(), None).await?;
+
+        assert!(Db::close().await);
+        assert!(!Db::is_initialized());
+        assert!(!Db::close().await);
+
+        // The next query reopens the same database file
+        let prompts = list_prompts().await?;
+        assert!(Db::is_initialized());
+        assert!(prompts.iter().any(|p| p.id == prompt.id));
+
+        delete_prompt(prompt.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_by_reference() -> Result<()> {
+        let _guard = setup_db().await?;
+        let review =
+            create_prompt("Mention Code Review".into(), None, "review".into(), None).await?;
+
+        for reference in [
+            review.id.as_str(),
+            "Mention Code Review",
+            "mention code review",
+            "mention-code-review",
+        ] {
+            let found = find_by_reference(reference).await?;
+            assert_eq!(
+                found.map(|p| p.id),
+                Some(review.id.clone()),
+                "{}",
+                reference
+            );
+        }
+        assert!(find_by_reference("mention-code").await?.is_none());
+
+        delete_prompt(review.id).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_moves_damaged_database_aside() -> Result<()> {
+        let _lock = DB_LOCK.lock().await;
+        let dir = tempdir()?;
+        let path = dir.path().join("test_prompts.db");
+
+        // Not a SQLite file, so opening it fails
+        std::fs::write(&path, "not a database")?;
+        Db::close().await;
+        Db::configure(&path);
+        assert!(list_prompts().await.is_err());
+
+        let backup = Db::rebuild().await?.expect("the damaged file was moved");
+        assert_eq!(std::fs::read_to_string(&backup)?, "not a database");
+        assert!(backup
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("test_prompts.db.broken-"));
+
+        // The new database works and starts empty
+        assert!(list_prompts().await?.is_empty());
+        create_prompt("After rebuild".into(), None, "content".into(), None).await?;
+        assert_eq!(list_prompts().await?.len(), 1);
+
+        Ok(())
+    }
 }
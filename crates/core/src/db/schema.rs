@@ -1,3 +1,21 @@
+//! Database schema and its resilient application
+//!
+//! Databases created by older plugin versions can be partially upgraded, so
+//! a statement in `SCHEMA` may fail against them. `apply` classifies each
+//! failure instead of aborting on the first one:
+//!
+//! - idempotency failures (the object already exists) are skipped,
+//! - a missing column with a known fixup in `COLUMN_FIXUPS` is added, then
+//!   the statement is retried,
+//! - anything else aborts with a `MigrationError` naming the statement and
+//!   suggesting `db.rebuild` (see `Db::rebuild`).
+//!
+//! Skips and fixups are reported as warnings.
+
+use sqlx::SqlitePool;
+
+use crate::errors::{AmpError, Result};
+
 pub const SCHEMA: &str = "
 -- Core prompts table
 CREATE TABLE IF NOT EXISTS prompts (
@@ -15,4 +33,258 @@ CREATE TABLE IF NOT EXISTS prompts (
 -- Indexes for performance
 CREATE INDEX IF NOT EXISTS idx_prompts_usage ON prompts(usage_count DESC);
 CREATE INDEX IF NOT EXISTS idx_prompts_updated ON prompts(updated_at DESC);
+CREATE INDEX IF NOT EXISTS idx_prompts_title ON prompts(title COLLATE NOCASE);
+
+-- Full-text index over prompt text, keyed by prompts.rowid
+-- Kept in sync by the write functions in db::prompts (no triggers, since
+-- statements here are split on semicolons)
+CREATE VIRTUAL TABLE IF NOT EXISTS prompts_fts USING fts5(title, description, content, tags);
+
+-- Files imported by prompts.import_from_directory
+CREATE TABLE IF NOT EXISTS prompt_sources (
+    path TEXT PRIMARY KEY,        -- Absolute path of the imported file
+    prompt_id TEXT NOT NULL,      -- prompts.id
+    content_hash TEXT NOT NULL,   -- SHA-256 of the file contents (hex)
+    imported_at INTEGER NOT NULL  -- Unix timestamp (seconds)
+);
+CREATE INDEX IF NOT EXISTS idx_prompt_sources_hash ON prompt_sources(content_hash);
+";
+
+/// Index prompts missing from `prompts_fts` (rows written by older versions)
+pub const FTS_BACKFILL: &str = "
+INSERT INTO prompts_fts (rowid, title, description, content, tags)
+SELECT rowid, title, description, content, tags FROM prompts
+WHERE rowid NOT IN (SELECT rowid FROM prompts_fts)
 ";
+
+/// Columns added after the first release, as `(column, ALTER statement)`
+///
+/// Applied when a schema statement fails with "no such column".
+pub const COLUMN_FIXUPS: &[(&str, &str)] = &[
+    (
+        "description",
+        "ALTER TABLE prompts ADD COLUMN description TEXT",
+    ),
+    ("tags", "ALTER TABLE prompts ADD COLUMN tags TEXT"),
+    (
+        "usage_count",
+        "ALTER TABLE prompts ADD COLUMN usage_count INTEGER DEFAULT 0",
+    ),
+    (
+        "last_used_at",
+        "ALTER TABLE prompts ADD COLUMN last_used_at INTEGER",
+    ),
+    (
+        "updated_at",
+        "ALTER TABLE prompts ADD COLUMN updated_at INTEGER NOT NULL DEFAULT 0",
+    ),
+];
+
+/// How a failed schema statement is handled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Failure {
+    /// The object already exists; safe to skip
+    AlreadyExists,
+    /// A column is missing; fixable if it has a `COLUMN_FIXUPS` entry
+    MissingColumn(String),
+    /// Anything else
+    Fatal,
+}
+
+/// Classify a schema statement error message
+pub fn classify(message: &str) -> Failure {
+    let lower = message.to_lowercase();
+
+    if lower.contains("already exists") || lower.contains("duplicate column name") {
+        return Failure::AlreadyExists;
+    }
+
+    if let Some(rest) = lower.split("no such column:").nth(1) {
+        // "no such column: usage_count" or "no such column: prompts.usage_count"
+        let column = rest
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .trim_matches(|c: char| !c.is_alphanumeric() && c != '_');
+        if !column.is_empty() {
+            return Failure::MissingColumn(column.to_string());
+        }
+    }
+
+    Failure::Fatal
+}
+
+/// Fixup statement for a missing column, if known
+pub fn column_fixup(column: &str) -> Option<&'static str> {
+    COLUMN_FIXUPS
+        .iter()
+        .find(|(name, _)| *name == column)
+        .map(|(_, sql)| *sql)
+}
+
+/// Apply `SCHEMA` statement by statement, then `COLUMN_FIXUPS`; returns warnings
+///
+/// Statements are numbered from 1 in error messages and warnings.
+pub async fn apply(pool: &SqlitePool) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    let statements = SCHEMA.split(';').filter(|s| !s.trim().is_empty());
+
+    for (index, statement) in statements.enumerate().map(|(i, s)| (i + 1, s)) {
+        let Err(err) = sqlx::query(statement).execute(pool).await else {
+            continue;
+        };
+        let message = error_message(&err);
+
+        match classify(&message) {
+            Failure::AlreadyExists => {
+                warnings.push(format!("Schema statement {} skipped: {}", index, message));
+            },
+            Failure::MissingColumn(column) => {
+                let Some(fixup) = column_fixup(&column) else {
+                    return Err(fatal(index, &message));
+                };
+                sqlx::query(fixup)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| fatal(index, &error_message(&e)))?;
+                sqlx::query(statement)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| fatal(index, &error_message(&e)))?;
+                warnings.push(format!(
+                    "Schema statement {} needed missing column '{}'; added it",
+                    index, column
+                ));
+            },
+            Failure::Fatal => return Err(fatal(index, &message)),
+        }
+    }
+
+    // Columns no statement above depends on (e.g. `description`) are added
+    // here; on current databases every fixup is a duplicate column
+    for (column, fixup) in COLUMN_FIXUPS {
+        match sqlx::query(fixup).execute(pool).await {
+            Ok(_) => warnings.push(format!("Added missing column '{}'", column)),
+            Err(err) => {
+                let message = error_message(&err);
+                if classify(&message) != Failure::AlreadyExists {
+                    return Err(AmpError::MigrationError(format!(
+                        "Adding column '{}' failed: {}. The database may be damaged; run `db.rebuild` to move it aside and start a new one",
+                        column, message
+                    )));
+                }
+            },
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// Database-reported message, without sqlx's wrapping
+fn error_message(err: &sqlx::Error) -> String {
+    match err {
+        sqlx::Error::Database(db) => db.message().to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn fatal(index: usize, message: &str) -> AmpError {
+    AmpError::MigrationError(format!(
+        "Schema statement {} failed: {}. The database may be damaged; run `db.rebuild` to move it aside and start a new one",
+        index, message
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+    /// Fresh database in a temp dir, seeded with `setup` statements
+    async fn temp_pool(setup: &[&str]) -> (tempfile::TempDir, SqlitePool) {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(dir.path().join("prompts.db"))
+                    .create_if_missing(true),
+            )
+            .await
+            .unwrap();
+        for statement in setup {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        (dir, pool)
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            classify("index idx_prompts_usage already exists"),
+            Failure::AlreadyExists
+        );
+        assert_eq!(
+            classify("duplicate column name: description"),
+            Failure::AlreadyExists
+        );
+        assert_eq!(
+            classify("no such column: usage_count"),
+            Failure::MissingColumn("usage_count".into())
+        );
+        assert_eq!(
+            classify("no such column: prompts.last_used_at"),
+            Failure::MissingColumn("last_used_at".into())
+        );
+        assert_eq!(classify("database disk image is malformed"), Failure::Fatal);
+    }
+
+    #[tokio::test]
+    async fn test_fresh_database_has_no_warnings() {
+        let (_dir, pool) = temp_pool(&[]).await;
+        assert!(apply(&pool).await.unwrap().is_empty());
+        // Applying again is a no-op
+        assert!(apply(&pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_half_migrated_database_is_repaired() {
+        // A prompts table from before usage tracking existed
+        let (_dir, pool) = temp_pool(&[
+            "CREATE TABLE prompts (id TEXT PRIMARY KEY, title TEXT NOT NULL,
+             content TEXT NOT NULL, created_at INTEGER NOT NULL)",
+            "INSERT INTO prompts VALUES ('a', 'Old', 'old prompt', 1)",
+        ])
+        .await;
+
+        let warnings = apply(&pool).await.unwrap();
+        assert!(warnings.iter().any(|w| w.contains("'usage_count'")));
+        assert!(warnings.iter().any(|w| w.contains("'updated_at'")));
+        assert!(warnings.contains(&"Added missing column 'description'".to_string()));
+
+        // Repaired databases apply cleanly from then on
+        assert!(apply(&pool).await.unwrap().is_empty());
+
+        // Existing rows survive and the fixed-up columns are usable
+        let (usage, updated): (i64, i64) =
+            sqlx::query_as("SELECT usage_count, updated_at FROM prompts WHERE id = 'a'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((usage, updated), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_unfixable_failure_names_statement() {
+        // A view can't be indexed, so the first index statement fails
+        let (_dir, pool) = temp_pool(&["CREATE VIEW prompts AS SELECT 1 AS id"]).await;
+
+        let err = apply(&pool).await.unwrap_err();
+        assert_eq!(err.category(), "migration");
+        assert!(err.to_string().contains("Schema statement 2 failed"));
+    }
+}
@@ -3,6 +3,7 @@
 //! This module defines error types that bridge Rust and Lua, ensuring
 //! error messages are user-friendly across the FFI boundary.
 
+use serde_json::{json, Value};
 use thiserror::Error;
 
 /// Result type alias for amp-extras operations
@@ -27,6 +28,10 @@ pub enum AmpError {
     #[error("Database error: {0}")]
     DatabaseError(#[from] sqlx::Error),
 
+    /// Schema migration failed while opening the database
+    #[error("Database migration failed: {0}")]
+    MigrationError(String),
+
     /// I/O error
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
@@ -35,6 +40,10 @@ pub enum AmpError {
     #[error("Amp CLI error: {0}")]
     AmpCliError(String),
 
+    /// Amp CLI binary could not be found
+    #[error("Amp CLI not found: {0}")]
+    AmpCliNotFound(String),
+
     /// Thread parsing error
     #[error("Failed to parse thread file: {0}")]
     ThreadParseError(String),
@@ -79,11 +88,15 @@ impl AmpError {
     /// Get user-friendly error message for display in Neovim
     pub fn user_message(&self) -> String {
         match self {
-            AmpError::CommandNotFound(cmd) => {
-                format!(
+            AmpError::CommandNotFound(cmd) => match crate::commands::suggest_command(cmd) {
+                Some(suggestion) => format!(
+                    "Command '{}' not found. Did you mean '{}'?",
+                    cmd, suggestion
+                ),
+                None => format!(
                     "Command '{}' not found. Run :AmpHelp for available commands.",
                     cmd
-                )
+                ),
             },
             AmpError::InvalidArgs { command, reason } => {
                 format!("Invalid arguments for '{}': {}", command, reason)
@@ -91,9 +104,21 @@ impl AmpError {
             AmpError::AmpCliError(msg) => {
                 format!("Amp CLI error: {}", msg)
             },
+            AmpError::AmpCliNotFound(binary) => {
+                format!(
+                    "Amp CLI not found ('{}' is not on PATH). Install it from https://ampcode.com",
+                    binary
+                )
+            },
             AmpError::DatabaseError(err) => {
                 format!("Database error: {}", err)
             },
+            AmpError::MigrationError(msg) => {
+                format!(
+                    "Database migration failed: {}. Prompts are unavailable until this is fixed.",
+                    msg
+                )
+            },
             _ => self.to_string(),
         }
     }
@@ -105,8 +130,10 @@ impl AmpError {
             AmpError::InvalidArgs { .. } => "arguments",
             AmpError::SerdeError(_) => "serialization",
             AmpError::DatabaseError(_) => "database",
+            AmpError::MigrationError(_) => "migration",
             AmpError::IoError(_) => "io",
             AmpError::AmpCliError(_) => "amp_cli",
+            AmpError::AmpCliNotFound(_) => "amp_cli_not_found",
             AmpError::ThreadParseError(_) => "thread_parse",
             AmpError::ConfigError(_) => "config",
             AmpError::ValidationError(_) => "validation",
@@ -114,6 +141,36 @@ impl AmpError {
             AmpError::Other(_) => "other",
         }
     }
+
+    /// JSON-RPC style error code
+    ///
+    /// Uses the standard codes where one fits (method not found, invalid
+    /// params, parse error) and -32603 (internal error) otherwise.
+    pub fn code(&self) -> i64 {
+        match self {
+            AmpError::CommandNotFound(_) => -32601,
+            AmpError::InvalidArgs { .. } | AmpError::ValidationError(_) => -32602,
+            AmpError::SerdeError(_) | AmpError::ConversionError(_) => -32700,
+            _ => -32603,
+        }
+    }
+
+    /// Structured details for errors that carry more than a message
+    pub fn data(&self) -> Option<Value> {
+        match self {
+            AmpError::CommandNotFound(command) => {
+                let mut data = json!({ "command": command });
+                if let Some(suggestion) = crate::commands::suggest_command(command) {
+                    data["did_you_mean"] = json!(suggestion);
+                }
+                Some(data)
+            },
+            AmpError::InvalidArgs { command, reason } => {
+                Some(json!({ "command": command, "reason": reason }))
+            },
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -149,6 +206,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_error_code() {
+        assert_eq!(AmpError::CommandNotFound("test".to_string()).code(), -32601);
+        assert_eq!(
+            AmpError::InvalidArgs {
+                command: "test".to_string(),
+                reason: "bad".to_string(),
+            }
+            .code(),
+            -32602
+        );
+        assert_eq!(AmpError::Other("x".to_string()).code(), -32603);
+    }
+
+    #[test]
+    fn test_error_data() {
+        let err = AmpError::InvalidArgs {
+            command: "prompts.list".to_string(),
+            reason: "bad".to_string(),
+        };
+        assert_eq!(
+            err.data(),
+            Some(json!({ "command": "prompts.list", "reason": "bad" }))
+        );
+        assert_eq!(AmpError::Other("x".to_string()).data(), None);
+    }
+
     #[test]
     fn test_from_string() {
         let err: AmpError = "test error".into();
@@ -5,28 +5,37 @@
 //! - Autocomplete
 //! - Error conversion to Lua-friendly formats
 
-use std::sync::OnceLock;
+use std::{collections::HashMap, sync::RwLock};
 
-use nvim_oxi::{serde::Deserializer, Dictionary, Object};
+use nvim_oxi::{serde::Deserializer, Array, Dictionary, Object};
 use serde::Deserialize;
 use serde_json::Value;
 
 use crate::{
     commands,
-    db::Db,
+    db::{prompts, Db},
     errors::{AmpError, Result},
-    runtime,
+    format, lint, runtime, threads,
 };
 
 /// Plugin configuration
 #[derive(Debug, Clone, Default, Deserialize)]
 struct Config {
-    // Add configuration fields here if needed in the future
-    // Previously had auto_start for server
+    /// Outgoing message template overrides (see `format`)
+    #[serde(default)]
+    templates: HashMap<String, String>,
+
+    /// Pre-send checks (see `lint`)
+    #[serde(default)]
+    lint: lint::LintConfig,
+
+    /// Amp CLI thread directory override (see `threads::storage`)
+    #[serde(default)]
+    threads_dir: Option<std::path::PathBuf>,
 }
 
-/// Global config storage
-static CONFIG: OnceLock<Config> = OnceLock::new();
+/// Global config storage (replaced on each setup call)
+static CONFIG: RwLock<Option<Config>> = RwLock::new(None);
 
 /// Main FFI entry point for command execution
 ///
@@ -78,17 +87,39 @@ pub fn autocomplete(kind: String, prefix: String) -> nvim_oxi::Result<Vec<String
     }
 }
 
+/// Drain pending warnings (e.g. deprecated command names)
+///
+/// Called from Lua as: `ffi.take_warnings()` after each `ffi.call`, which
+/// shows them with `vim.notify`.
+pub fn take_warnings() -> nvim_oxi::Result<Vec<String>> {
+    Ok(commands::take_warnings())
+}
+
 // ============================================================================
 // Plugin Setup
 // ============================================================================
 
 /// Setup the plugin with configuration
 ///
-/// Called from Lua as: `ffi.setup({})`
+/// Called from Lua as: `ffi.setup({ templates = { selection = "..." } })`
+///
+/// A config that doesn't deserialize (e.g. `lint = true`), invalid message
+/// templates and invalid lint patterns are rejected with an error object.
+/// Only records the database path; the database itself is opened and
+/// migrated on first use (or via the `db.warmup` command) so setup never
+/// blocks startup on disk I/O.
 ///
 /// Returns:
 /// ```lua
-/// { success = true }
+/// {
+///   success = true,
+///   db = { initialized = false, path = "~/.config/amp-extras/prompts.db" },
+///   config = {
+///     templates = { selection = "{{content}}", ... },
+///     lint = { max_bytes = 1048576, allow_secrets = false, builtin_patterns = {...}, patterns = {} },
+///     db_path_source = "home", -- or "XDG_CONFIG_HOME", "cwd"
+///   },
+/// }
 /// ```
 /// Or on error:
 /// ```lua
@@ -99,32 +130,90 @@ pub fn autocomplete(kind: String, prefix: String) -> nvim_oxi::Result<Vec<String
 /// ```
 pub fn setup(config_obj: Object) -> nvim_oxi::Result<Object> {
     // Deserialize config from Lua
-    let config: Config = Config::deserialize(Deserializer::new(config_obj)).unwrap_or_default();
+    let config = match Config::deserialize(Deserializer::new(config_obj)) {
+        Ok(config) => config,
+        Err(e) => {
+            return Ok(create_error_object(&AmpError::ConfigError(format!(
+                "Invalid setup() options: {}",
+                e
+            ))))
+        },
+    };
+
+    // Validate templates before accepting the config
+    if let Err(e) = format::configure(&config.templates) {
+        return Ok(create_error_object(&e));
+    }
+    if let Err(e) = lint::configure(&config.lint) {
+        return Ok(create_error_object(&e));
+    }
+
+    threads::storage::configure(config.threads_dir.clone());
 
-    // Store config (first call wins)
-    let _ = CONFIG.set(config);
+    // Store config; setup may run again after `plugin.reload_prepare`
+    *CONFIG.write().unwrap_or_else(|e| e.into_inner()) = Some(config);
 
-    // Initialize Database
+    // Record database path
     // Use XDG_CONFIG_HOME or ~/.config style path
     // On macOS, dirs::config_dir defaults to Application Support, but we prefer ~/.config
-    let config_dir = std::env::var("XDG_CONFIG_HOME")
-        .map(std::path::PathBuf::from)
-        .ok() // Convert Result to Option
-        .or_else(|| dirs::home_dir().map(|h| h.join(".config")))
-        .unwrap_or_else(|| std::path::PathBuf::from("."));
+    let (config_dir, db_source) = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(dir) => (std::path::PathBuf::from(dir), "XDG_CONFIG_HOME"),
+        Err(_) => match dirs::home_dir() {
+            Some(home) => (home.join(".config"), "home"),
+            None => (std::path::PathBuf::from("."), "cwd"),
+        },
+    };
 
-    let db_path = config_dir.join("amp-extras/prompts.db");
+    Db::configure(config_dir.join("amp-extras/prompts.db"));
 
-    let db_path_str = db_path.to_str().unwrap_or("prompts.db");
+    let db_path = Db::path()
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or_default();
 
-    if let Err(e) = runtime::block_on(Db::init(db_path_str)) {
-        return Ok(create_error_object(&e));
-    }
+    // Schema warnings exist here only if the database was opened before
+    // this setup (e.g. setup running again); otherwise they arrive with the
+    // first command's warnings
+    let warnings = Array::from_iter(Db::take_schema_warnings());
+
+    let db = Dictionary::from_iter([
+        ("initialized", Object::from(Db::is_initialized())),
+        ("path", Object::from(db_path)),
+        ("warnings", Object::from(warnings)),
+    ]);
 
-    let result = Dictionary::from_iter([("success", Object::from(true))]);
+    let result = Dictionary::from_iter([
+        ("success", Object::from(true)),
+        ("db", Object::from(db)),
+        ("config", resolved_config(db_source)),
+    ]);
     Ok(Object::from(result))
 }
 
+/// Effective configuration after setup, for display in Lua/`:checkhealth`
+///
+/// `db_source` records where the database directory came from, since
+/// `XDG_CONFIG_HOME` silently overrides the `~/.config` default. Secret-like
+/// values are redacted (see `lint::describe`).
+fn resolved_config(db_source: &str) -> Object {
+    use nvim_oxi::serde::Serializer;
+    use serde::Serialize;
+
+    let templates: serde_json::Map<String, Value> = format::active_sources()
+        .into_iter()
+        .map(|(name, source)| (name.to_string(), Value::from(source)))
+        .collect();
+
+    let config = serde_json::json!({
+        "templates": templates,
+        "lint": lint::describe(),
+        "db_path_source": db_source,
+    });
+
+    config
+        .serialize(Serializer::new())
+        .unwrap_or_else(|_| Object::from(Dictionary::new()))
+}
+
 // ============================================================================
 // Internal Helpers
 // ============================================================================
@@ -136,9 +225,22 @@ fn dispatch_command(command: &str, args: Value) -> Result<Value> {
     commands::dispatch(command, args)
 }
 
-/// Internal autocomplete implementation (stub - completion handled in Lua via blink.cmp)
-fn autocomplete_impl(_kind: &str, _prefix: &str) -> Result<Vec<String>> {
-    Ok(vec![])
+/// Maximum number of autocomplete items returned
+const AUTOCOMPLETE_LIMIT: u32 = 20;
+
+/// Internal autocomplete implementation
+///
+/// Prompt titles are looked up by prefix in the database; other kinds are
+/// completed in Lua via blink.cmp.
+fn autocomplete_impl(kind: &str, prefix: &str) -> Result<Vec<String>> {
+    match kind {
+        "prompt" => {
+            let prompts =
+                runtime::block_on(prompts::find_by_title_prefix(prefix, AUTOCOMPLETE_LIMIT))?;
+            Ok(prompts.into_iter().map(|p| p.title).collect())
+        },
+        _ => Ok(vec![]),
+    }
 }
 
 /// Create a structured error object for Lua
@@ -147,12 +249,21 @@ fn autocomplete_impl(_kind: &str, _prefix: &str) -> Result<Vec<String>> {
 /// - `error`: true (marker that this is an error response)
 /// - `message`: user-friendly error message
 /// - `category`: error category for logging/handling
+/// - `code`: JSON-RPC style error code (see `AmpError::code`)
+/// - `data`: structured details, only for errors that have them
 fn create_error_object(err: &AmpError) -> Object {
-    let error_dict = Dictionary::from_iter([
+    use nvim_oxi::serde::Serializer;
+    use serde::Serialize;
+
+    let mut error_dict = Dictionary::from_iter([
         ("error", Object::from(true)),
         ("message", Object::from(err.user_message())),
         ("category", Object::from(err.category())),
+        ("code", Object::from(err.code())),
     ]);
+    if let Some(data) = err.data().and_then(|d| d.serialize(Serializer::new()).ok()) {
+        error_dict.insert("data", data);
+    }
     Object::from(error_dict)
 }
 
@@ -305,4 +416,65 @@ mod tests {
             <String as FromObject>::from_object(dict.get("category").unwrap().clone()).unwrap();
         assert_eq!(cat, "command");
     }
+
+    #[test]
+    fn test_create_error_object_code_command_not_found() {
+        let err = AmpError::CommandNotFound("test.command".to_string());
+        let dict = Dictionary::from_object(create_error_object(&err)).unwrap();
+
+        let code = <i64 as FromObject>::from_object(dict.get("code").unwrap().clone()).unwrap();
+        assert_eq!(code, -32601);
+
+        let data = Dictionary::from_object(dict.get("data").unwrap().clone()).unwrap();
+        let command =
+            <String as FromObject>::from_object(data.get("command").unwrap().clone()).unwrap();
+        assert_eq!(command, "test.command");
+    }
+
+    #[test]
+    fn test_create_error_object_code_invalid_args() {
+        let err = AmpError::InvalidArgs {
+            command: "prompts.list".to_string(),
+            reason: "page_size must be between 1 and 500".to_string(),
+        };
+        let dict = Dictionary::from_object(create_error_object(&err)).unwrap();
+
+        let code = <i64 as FromObject>::from_object(dict.get("code").unwrap().clone()).unwrap();
+        assert_eq!(code, -32602);
+
+        let data = Dictionary::from_object(dict.get("data").unwrap().clone()).unwrap();
+        let reason =
+            <String as FromObject>::from_object(data.get("reason").unwrap().clone()).unwrap();
+        assert!(reason.contains("page_size"));
+    }
+
+    #[test]
+    fn test_create_error_object_without_data() {
+        let err = AmpError::Other("boom".to_string());
+        let dict = Dictionary::from_object(create_error_object(&err)).unwrap();
+
+        assert!(dict.get("code").is_some());
+        assert!(dict.get("data").is_none());
+    }
+
+    // ========================================
+    // resolved_config() tests
+    // ========================================
+
+    #[test]
+    fn test_resolved_config_structure() {
+        let dict = Dictionary::from_object(resolved_config("home")).unwrap();
+
+        let templates = Dictionary::from_object(dict.get("templates").unwrap().clone()).unwrap();
+        assert!(templates.get("selection").is_some());
+        assert!(templates.get("file_ref").is_some());
+
+        let lint = Dictionary::from_object(dict.get("lint").unwrap().clone()).unwrap();
+        assert!(lint.get("max_bytes").is_some());
+
+        let source =
+            <String as FromObject>::from_object(dict.get("db_path_source").unwrap().clone())
+                .unwrap();
+        assert_eq!(source, "home");
+    }
 }
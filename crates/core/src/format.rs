@@ -0,0 +1,355 @@
+//! Outgoing message templates
+//!
+//! Formats the text pushed to the Amp prompt by the send commands. Each
+//! template is a string with `{{placeholder}}` markers that are substituted
+//! in a single pass, so substituted values are inserted verbatim even when
+//! they contain `{{` or `}}` themselves.
+//!
+//! Templates can be overridden from `setup({ templates = { ... } })`; invalid
+//! templates (unknown placeholder, unbalanced braces) are rejected with a
+//! `ConfigError` naming the template.
+
+use std::{collections::HashMap, sync::RwLock};
+
+use once_cell::sync::Lazy;
+use serde_json::{Map, Value};
+
+use crate::errors::{AmpError, Result};
+
+/// Placeholders that may appear in any template
+pub const PLACEHOLDERS: &[&str] = &[
+    "path",
+    "start_line",
+    "end_line",
+    "content",
+    "filetype",
+    "severity",
+    "message",
+    "range",
+    "absolute",
+];
+
+/// Default template for `send_selection`: the selected text as-is
+pub const DEFAULT_SELECTION: &str = "{{content}}";
+
+/// Default template for diagnostic sends
+pub const DEFAULT_DIAGNOSTIC: &str = "@{{path}}#L{{start_line}} {{severity}}: {{message}}";
+
+/// Default template for file references (`@file.rs#L10-20`)
+///
+/// `{{range}}` is the `#L..` suffix computed by the caller (empty for a whole
+/// file reference). `{{absolute}}` is `true` when `{{path}}` is absolute
+/// because the file is outside the working directory.
+pub const DEFAULT_FILE_REF: &str = "@{{path}}{{range}}";
+
+/// A parsed template segment
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder(&'static str),
+}
+
+/// A validated template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /// Parse and validate a template
+    ///
+    /// `name` is only used for error messages.
+    pub fn parse(name: &str, source: &str) -> Result<Self> {
+        let invalid = |reason: String| {
+            AmpError::ConfigError(format!("Invalid template '{}': {}", name, reason))
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = source;
+
+        while let Some(open) = rest.find("{{") {
+            let literal = &rest[..open];
+            if literal.contains("}}") {
+                return Err(invalid("unexpected '}}'".to_string()));
+            }
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(literal.to_string()));
+            }
+
+            let after = &rest[open + 2..];
+            let close = after
+                .find("}}")
+                .ok_or_else(|| invalid("unclosed '{{'".to_string()))?;
+            let key = after[..close].trim();
+            if key.contains("{{") {
+                return Err(invalid("nested '{{'".to_string()));
+            }
+
+            let placeholder = PLACEHOLDERS
+                .iter()
+                .find(|p| **p == key)
+                .ok_or_else(|| invalid(format!("unknown placeholder '{{{{{}}}}}'", key)))?;
+            segments.push(Segment::Placeholder(placeholder));
+
+            rest = &after[close + 2..];
+        }
+
+        if rest.contains("}}") {
+            return Err(invalid("unexpected '}}'".to_string()));
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Literal(rest.to_string()));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    /// The template text as configured
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Render the template with the given values
+    ///
+    /// Missing or null values render as empty strings; numbers and booleans
+    /// use their JSON representation.
+    pub fn render(&self, values: &Map<String, Value>) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Placeholder(key) => match values.get(*key) {
+                    Some(Value::String(s)) => out.push_str(s),
+                    Some(Value::Null) | None => {},
+                    Some(other) => out.push_str(&other.to_string()),
+                },
+            }
+        }
+        out
+    }
+}
+
+/// The configured set of templates
+#[derive(Debug, Clone)]
+pub struct Templates {
+    pub selection: Template,
+    pub diagnostic: Template,
+    pub file_ref: Template,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self {
+            selection: Template::parse("selection", DEFAULT_SELECTION)
+                .expect("default selection template is valid"),
+            diagnostic: Template::parse("diagnostic", DEFAULT_DIAGNOSTIC)
+                .expect("default diagnostic template is valid"),
+            file_ref: Template::parse("file_ref", DEFAULT_FILE_REF)
+                .expect("default file_ref template is valid"),
+        }
+    }
+}
+
+impl Templates {
+    /// Build templates from user overrides, falling back to defaults
+    pub fn from_overrides(overrides: &HashMap<String, String>) -> Result<Self> {
+        let mut templates = Self::default();
+        for (name, source) in overrides {
+            let template = Template::parse(name, source)?;
+            match name.as_str() {
+                "selection" => templates.selection = template,
+                "diagnostic" => templates.diagnostic = template,
+                "file_ref" => templates.file_ref = template,
+                _ => {
+                    return Err(AmpError::ConfigError(format!(
+                        "Unknown template '{}'",
+                        name
+                    )))
+                },
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Look up a template by name
+    pub fn get(&self, name: &str) -> Result<&Template> {
+        match name {
+            "selection" => Ok(&self.selection),
+            "diagnostic" => Ok(&self.diagnostic),
+            "file_ref" => Ok(&self.file_ref),
+            _ => Err(AmpError::ValidationError(format!(
+                "Unknown template '{}'",
+                name
+            ))),
+        }
+    }
+}
+
+/// Active templates, replaced by `configure`
+static TEMPLATES: Lazy<RwLock<Templates>> = Lazy::new(|| RwLock::new(Templates::default()));
+
+/// Validate and install user template overrides
+///
+/// On error the previously active templates are kept.
+pub fn configure(overrides: &HashMap<String, String>) -> Result<()> {
+    let templates = Templates::from_overrides(overrides)?;
+    *TEMPLATES.write().unwrap_or_else(|e| e.into_inner()) = templates;
+    Ok(())
+}
+
+/// Sources of the active templates, by name
+pub fn active_sources() -> HashMap<&'static str, String> {
+    let templates = TEMPLATES.read().unwrap_or_else(|e| e.into_inner());
+    HashMap::from([
+        ("selection", templates.selection.source().to_string()),
+        ("diagnostic", templates.diagnostic.source().to_string()),
+        ("file_ref", templates.file_ref.source().to_string()),
+    ])
+}
+
+/// Render a configured template by name
+pub fn render(name: &str, values: &Map<String, Value>) -> Result<String> {
+    let templates = TEMPLATES.read().unwrap_or_else(|e| e.into_inner());
+    Ok(templates.get(name)?.render(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn values(v: Value) -> Map<String, Value> {
+        v.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_defaults_match_previous_formats() {
+        let templates = Templates::default();
+        let v = values(json!({
+            "path": "src/main.rs",
+            "range": "#L10-20",
+            "content": "fn main() {}",
+            "start_line": 10,
+            "severity": "ERROR",
+            "message": "oops"
+        }));
+
+        assert_eq!(templates.selection.render(&v), "fn main() {}");
+        assert_eq!(templates.file_ref.render(&v), "@src/main.rs#L10-20");
+        assert_eq!(
+            templates.diagnostic.render(&v),
+            "@src/main.rs#L10 ERROR: oops"
+        );
+    }
+
+    #[test]
+    fn test_render_substitutes_all_placeholders() {
+        let template = Template::parse(
+            "selection",
+            "Context from {{path}} ({{start_line}}-{{end_line}}):\n```{{filetype}}\n{{content}}\n```",
+        )
+        .unwrap();
+        let v = values(json!({
+            "path": "a.lua",
+            "start_line": 1,
+            "end_line": 3,
+            "filetype": "lua",
+            "content": "print(1)",
+        }));
+
+        assert_eq!(
+            template.render(&v),
+            "Context from a.lua (1-3):\n```lua\nprint(1)\n```"
+        );
+    }
+
+    #[test]
+    fn test_render_content_with_braces_is_verbatim() {
+        let template = Template::parse("selection", "> {{content}} <").unwrap();
+        let v = values(json!({ "content": "{{path}} and }} and {{", "path": "x"}));
+
+        assert_eq!(template.render(&v), "> {{path}} and }} and {{ <");
+    }
+
+    #[test]
+    fn test_render_missing_values_are_empty() {
+        let template = Template::parse("file_ref", "@{{path}}{{range}}").unwrap();
+        let v = values(json!({ "path": "a.rs", "range": null }));
+
+        assert_eq!(template.render(&v), "@a.rs");
+    }
+
+    #[test]
+    fn test_render_absolute_flag() {
+        let template = Template::parse("file_ref", "@{{path}}{{range}} ({{absolute}})").unwrap();
+
+        let v = values(json!({ "path": "/tmp/a.rs", "range": "#L2", "absolute": true }));
+        assert_eq!(template.render(&v), "@/tmp/a.rs#L2 (true)");
+
+        let v = values(json!({ "path": "a.rs", "range": "", "absolute": false }));
+        assert_eq!(template.render(&v), "@a.rs (false)");
+    }
+
+    #[test]
+    fn test_placeholder_whitespace_is_trimmed() {
+        let template = Template::parse("selection", "{{ content }}").unwrap();
+        assert_eq!(template.render(&values(json!({"content": "x"}))), "x");
+    }
+
+    #[test]
+    fn test_single_braces_are_literal() {
+        let template = Template::parse("selection", "{ {{content}} }").unwrap();
+        assert_eq!(template.render(&values(json!({"content": "x"}))), "{ x }");
+    }
+
+    #[test]
+    fn test_unknown_placeholder_rejected() {
+        let err = Template::parse("selection", "{{nope}}").unwrap_err();
+        assert_eq!(err.category(), "config");
+        assert!(err.to_string().contains("selection"));
+        assert!(err.to_string().contains("nope"));
+    }
+
+    #[test]
+    fn test_unbalanced_braces_rejected() {
+        assert!(Template::parse("t", "{{content").is_err());
+        assert!(Template::parse("t", "content}}").is_err());
+        assert!(Template::parse("t", "{{content}} }}").is_err());
+        assert!(Template::parse("t", "{{ {{content}} }}").is_err());
+    }
+
+    #[test]
+    fn test_from_overrides_rejects_unknown_template_name() {
+        let overrides = HashMap::from([("headline".to_string(), "{{path}}".to_string())]);
+        let err = Templates::from_overrides(&overrides).unwrap_err();
+        assert!(err.to_string().contains("headline"));
+    }
+
+    #[test]
+    fn test_from_overrides_names_invalid_template() {
+        let overrides = HashMap::from([("file_ref".to_string(), "{{path".to_string())]);
+        let err = Templates::from_overrides(&overrides).unwrap_err();
+        assert!(err.to_string().contains("file_ref"));
+    }
+
+    #[test]
+    fn test_template_keeps_source() {
+        let template = Template::parse("selection", "<{{ content }}>").unwrap();
+        assert_eq!(template.source(), "<{{ content }}>");
+    }
+
+    #[test]
+    fn test_from_overrides_keeps_other_defaults() {
+        let overrides = HashMap::from([("selection".to_string(), "<{{content}}>".to_string())]);
+        let templates = Templates::from_overrides(&overrides).unwrap();
+        let v = values(json!({"content": "x", "path": "a", "range": ""}));
+
+        assert_eq!(templates.selection.render(&v), "<x>");
+        assert_eq!(templates.file_ref.render(&v), "@a");
+    }
+}
@@ -17,12 +17,17 @@
 //! See ARCHITECTURE.md for complete documentation.
 
 // Module declarations
+pub mod cli;
 pub mod commands;
 
 pub mod db;
 pub mod errors;
 pub mod ffi;
+pub mod format;
+pub mod lint;
+pub mod mentions;
 pub mod runtime;
+pub mod threads;
 
 use nvim_oxi::{Dictionary, Function, Object};
 
@@ -62,6 +67,10 @@ fn amp_extras_core() -> nvim_oxi::Result<Dictionary> {
         }),
     );
     exports.insert("setup", Function::<Object, Object>::from_fn(ffi::setup));
+    exports.insert(
+        "take_warnings",
+        Function::<(), Vec<String>>::from_fn(|()| ffi::take_warnings()),
+    );
 
     Ok(exports)
 }
@@ -0,0 +1,281 @@
+//! `@prompt:` mention expansion for outgoing messages
+//!
+//! `@prompt:<title-or-id>` is replaced by the stored prompt's content before
+//! a message is sent. Rules:
+//!
+//! - A reference is an id, or a title with spaces written as hyphens
+//!   (`@prompt:code-review` finds "Code Review"); lookup is up to the resolver.
+//! - `@@prompt:name` is an escape and sends the literal `@prompt:name`.
+//! - Mentions inside expanded content expand too, at most `MAX_DEPTH` levels
+//!   deep; deeper mentions (e.g. from a prompt that mentions itself) are left
+//!   as written and reported as unresolved.
+//!
+//! Expansion is pure text substitution; the database lookup is passed in so
+//! the rules can be tested without one.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::Serialize;
+
+use crate::errors::Result;
+
+/// Deepest level at which mentions are expanded (the message is level 0)
+pub const MAX_DEPTH: usize = 3;
+
+/// `@prompt:` mentions, with an optional escaping `@` in group 1
+///
+/// References can't end in `.` or `-`, so trailing punctuation stays text.
+static MENTION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(@?)@prompt:([A-Za-z0-9_](?:[A-Za-z0-9_.-]*[A-Za-z0-9_])?)")
+        .expect("mention pattern is valid")
+});
+
+/// A prompt a mention resolved to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolved {
+    pub id: String,
+    pub title: String,
+    pub content: String,
+}
+
+/// A mention that was replaced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Expansion {
+    pub mention: String,
+    pub prompt_id: String,
+    pub title: String,
+    /// 1 for mentions in the message itself, 2 for mentions inside those, ...
+    pub depth: usize,
+}
+
+/// Why a mention was left as written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unresolved {
+    NotFound,
+    MaxDepth,
+}
+
+/// A mention left as written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnresolvedMention {
+    pub mention: String,
+    pub reason: Unresolved,
+}
+
+/// Expanded message with what was (and wasn't) substituted
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Expanded {
+    pub text: String,
+    pub expansions: Vec<Expansion>,
+    pub unresolved: Vec<UnresolvedMention>,
+}
+
+/// Expand mentions in `text`, looking references up with `resolve`
+pub fn expand<F>(text: &str, mut resolve: F) -> Result<Expanded>
+where
+    F: FnMut(&str) -> Result<Option<Resolved>>,
+{
+    let mut out = Expanded::default();
+    let text = expand_level(text, 1, &mut resolve, &mut out)?;
+    Ok(Expanded { text, ..out })
+}
+
+fn expand_level<F>(text: &str, depth: usize, resolve: &mut F, out: &mut Expanded) -> Result<String>
+where
+    F: FnMut(&str) -> Result<Option<Resolved>>,
+{
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for caps in MENTION.captures_iter(text) {
+        let whole = caps.get(0).expect("group 0 always matches");
+        result.push_str(&text[last..whole.start()]);
+        last = whole.end();
+        result.push_str(&replace(&caps, depth, resolve, out)?);
+    }
+
+    result.push_str(&text[last..]);
+    Ok(result)
+}
+
+/// Replacement for one mention match
+fn replace<F>(caps: &Captures, depth: usize, resolve: &mut F, out: &mut Expanded) -> Result<String>
+where
+    F: FnMut(&str) -> Result<Option<Resolved>>,
+{
+    let whole = &caps[0];
+    let reference = &caps[2];
+
+    // `@@prompt:x` -> literal `@prompt:x`
+    if !caps[1].is_empty() {
+        return Ok(whole[1..].to_string());
+    }
+
+    let unresolved = |out: &mut Expanded, reason: Unresolved| -> Result<String> {
+        out.unresolved.push(UnresolvedMention {
+            mention: reference.to_string(),
+            reason,
+        });
+        Ok(whole.to_string())
+    };
+
+    if depth > MAX_DEPTH {
+        return unresolved(out, Unresolved::MaxDepth);
+    }
+
+    let Some(prompt) = resolve(reference)? else {
+        return unresolved(out, Unresolved::NotFound);
+    };
+
+    out.expansions.push(Expansion {
+        mention: reference.to_string(),
+        prompt_id: prompt.id,
+        title: prompt.title,
+        depth,
+    });
+    expand_level(&prompt.content, depth + 1, resolve, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    /// Resolver over `(reference, content)` pairs; the reference doubles as
+    /// id and title
+    fn resolver(prompts: &[(&str, &str)]) -> impl FnMut(&str) -> Result<Option<Resolved>> {
+        let prompts: HashMap<String, String> = prompts
+            .iter()
+            .map(|(r, c)| (r.to_string(), c.to_string()))
+            .collect();
+        move |reference| {
+            Ok(prompts.get(reference).map(|content| Resolved {
+                id: reference.to_string(),
+                title: reference.to_string(),
+                content: content.clone(),
+            }))
+        }
+    }
+
+    #[test]
+    fn test_expands_mentions() {
+        let expanded = expand(
+            "Please @prompt:code-review this, then @prompt:tests.",
+            resolver(&[("code-review", "review carefully"), ("tests", "add tests")]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            expanded.text,
+            "Please review carefully this, then add tests."
+        );
+        assert_eq!(expanded.expansions.len(), 2);
+        assert_eq!(expanded.expansions[0].mention, "code-review");
+        assert_eq!(expanded.expansions[0].depth, 1);
+        assert!(expanded.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_text_without_mentions_is_unchanged() {
+        let text = "email me at dev@example.com about @prompt";
+        let expanded = expand(text, resolver(&[])).unwrap();
+        assert_eq!(expanded.text, text);
+        assert!(expanded.expansions.is_empty());
+        assert!(expanded.unresolved.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_mention_is_kept_and_reported() {
+        let expanded = expand("use @prompt:missing here", resolver(&[])).unwrap();
+        assert_eq!(expanded.text, "use @prompt:missing here");
+        assert_eq!(
+            expanded.unresolved,
+            vec![UnresolvedMention {
+                mention: "missing".into(),
+                reason: Unresolved::NotFound,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_double_at_escapes_mention() {
+        let expanded = expand(
+            "type @@prompt:review to mention @prompt:review",
+            resolver(&[("review", "REVIEW")]),
+        )
+        .unwrap();
+        assert_eq!(expanded.text, "type @prompt:review to mention REVIEW");
+        assert_eq!(expanded.expansions.len(), 1);
+    }
+
+    #[test]
+    fn test_escape_inside_prompt_content_stays_literal() {
+        let expanded = expand(
+            "@prompt:docs",
+            resolver(&[("docs", "write @@prompt:name"), ("name", "NAME")]),
+        )
+        .unwrap();
+        assert_eq!(expanded.text, "write @prompt:name");
+        assert_eq!(expanded.expansions.len(), 1);
+    }
+
+    #[test]
+    fn test_nested_mentions_expand() {
+        let expanded = expand(
+            "@prompt:a",
+            resolver(&[("a", "A[@prompt:b]"), ("b", "B[@prompt:c]"), ("c", "C")]),
+        )
+        .unwrap();
+        assert_eq!(expanded.text, "A[B[C]]");
+        let depths: Vec<_> = expanded.expansions.iter().map(|e| e.depth).collect();
+        assert_eq!(depths, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_expansion_stops_after_max_depth() {
+        let expanded = expand(
+            "@prompt:a",
+            resolver(&[
+                ("a", "A[@prompt:b]"),
+                ("b", "B[@prompt:c]"),
+                ("c", "C[@prompt:d]"),
+                ("d", "D"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(expanded.text, "A[B[C[@prompt:d]]]");
+        assert_eq!(expanded.expansions.len(), MAX_DEPTH);
+        assert_eq!(
+            expanded.unresolved,
+            vec![UnresolvedMention {
+                mention: "d".into(),
+                reason: Unresolved::MaxDepth,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_self_mention_terminates() {
+        let expanded = expand("@prompt:loop", resolver(&[("loop", "x @prompt:loop")])).unwrap();
+        assert_eq!(expanded.text, "x x x @prompt:loop");
+        assert_eq!(expanded.unresolved[0].reason, Unresolved::MaxDepth);
+    }
+
+    #[test]
+    fn test_trailing_punctuation_is_not_part_of_reference() {
+        let expanded = expand(
+            "(@prompt:review-1.) and @prompt:v1.2-",
+            resolver(&[("review-1", "R"), ("v1.2", "V")]),
+        )
+        .unwrap();
+        assert_eq!(expanded.text, "(R.) and V-");
+    }
+
+    #[test]
+    fn test_resolver_errors_propagate() {
+        let result = expand("@prompt:x", |_| Err("db down".into()));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,3 @@
+//! Amp CLI thread files
+
+pub mod storage;
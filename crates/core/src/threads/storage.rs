@@ -0,0 +1,275 @@
+//! Location of the Amp CLI's thread files
+//!
+//! Resolved in order from:
+//!
+//! 1. `setup({ threads_dir = ... })`
+//! 2. `$AMP_DATA_HOME/threads`
+//! 3. Platform defaults: the OS data dir (`$XDG_DATA_HOME` or
+//!    `~/.local/share` on Linux, `~/Library/Application Support` on macOS)
+//!    joined with `amp/threads`, then `~/.local/share/amp/threads`
+//!
+//! An explicitly chosen directory (1 or 2) must be a valid thread store or
+//! resolution fails; defaults are tried until one is. A directory is a
+//! valid store if it exists and the `*.json` files sampled from it are
+//! objects with a thread `id`. An empty directory is valid (no threads yet).
+
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use serde::Serialize;
+
+use crate::errors::{AmpError, Result};
+
+/// Environment variable overriding the Amp data directory
+pub const DATA_HOME_ENV: &str = "AMP_DATA_HOME";
+
+/// Thread files checked when validating a store
+const SAMPLE_FILES: usize = 5;
+
+/// `threads_dir` from setup, replaced by `configure`
+static CONFIGURED: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Where the thread directory came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Config,
+    Env,
+    PlatformDefault,
+}
+
+/// Resolved thread store, as reported by `threads.storage_info`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StorageInfo {
+    pub path: PathBuf,
+    pub source: Source,
+    /// Number of `*.json` thread files
+    pub file_count: u64,
+    /// Combined size of the thread files in bytes
+    pub total_bytes: u64,
+}
+
+/// Record the `threads_dir` setup option (`None` to use detection)
+pub fn configure(dir: Option<PathBuf>) {
+    *CONFIGURED.write().unwrap_or_else(|e| e.into_inner()) = dir;
+}
+
+/// Resolve the thread directory from setup, environment and defaults
+///
+/// Fails with a single `ConfigError` describing what was tried.
+pub fn resolve() -> Result<(PathBuf, Source)> {
+    let configured = CONFIGURED.read().unwrap_or_else(|e| e.into_inner()).clone();
+    resolve_from(
+        configured.as_deref(),
+        std::env::var_os(DATA_HOME_ENV),
+        &default_candidates(),
+    )
+}
+
+/// Resolve and describe the thread store
+pub fn info() -> Result<StorageInfo> {
+    let (path, source) = resolve()?;
+    let (file_count, total_bytes) = thread_files(&path)?
+        .iter()
+        .filter_map(|file| file.metadata().ok())
+        .fold((0, 0), |(count, bytes), meta| {
+            (count + 1, bytes + meta.len())
+        });
+
+    Ok(StorageInfo {
+        path,
+        source,
+        file_count,
+        total_bytes,
+    })
+}
+
+fn resolve_from(
+    configured: Option<&Path>,
+    data_home: Option<OsString>,
+    defaults: &[PathBuf],
+) -> Result<(PathBuf, Source)> {
+    if let Some(dir) = configured {
+        validate(dir).map_err(|reason| {
+            AmpError::ConfigError(format!("threads_dir {}: {}", dir.display(), reason))
+        })?;
+        return Ok((dir.to_path_buf(), Source::Config));
+    }
+
+    if let Some(home) = data_home.filter(|h| !h.is_empty()) {
+        let dir = PathBuf::from(home).join("threads");
+        validate(&dir).map_err(|reason| {
+            AmpError::ConfigError(format!("{} ({}): {}", DATA_HOME_ENV, dir.display(), reason))
+        })?;
+        return Ok((dir, Source::Env));
+    }
+
+    let mut tried = Vec::new();
+    for dir in defaults {
+        match validate(dir) {
+            Ok(()) => return Ok((dir.clone(), Source::PlatformDefault)),
+            Err(reason) => tried.push(format!("{} ({})", dir.display(), reason)),
+        }
+    }
+
+    Err(AmpError::ConfigError(format!(
+        "Amp thread directory not found; tried {}. Set threads_dir in setup() or {}",
+        if tried.is_empty() {
+            "no default locations".to_string()
+        } else {
+            tried.join(", ")
+        },
+        DATA_HOME_ENV
+    )))
+}
+
+/// Platform default locations, most specific first
+fn default_candidates() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(data) = dirs::data_dir() {
+        candidates.push(data.join("amp").join("threads"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        let xdg_default = home.join(".local/share/amp/threads");
+        if !candidates.contains(&xdg_default) {
+            candidates.push(xdg_default);
+        }
+    }
+    candidates
+}
+
+/// Check that `dir` looks like a thread store; `Err` holds the reason
+fn validate(dir: &Path) -> std::result::Result<(), String> {
+    if !dir.is_dir() {
+        return Err("not a directory".to_string());
+    }
+
+    let files = thread_files(dir).map_err(|e| e.to_string())?;
+    for file in files.iter().take(SAMPLE_FILES) {
+        if !looks_like_thread(file) {
+            return Err(format!("{} is not an Amp thread file", file.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a file holds a JSON object with a thread id
+fn looks_like_thread(path: &Path) -> bool {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+        .and_then(|value| value.get("id")?.as_str().map(|id| id.starts_with("T-")))
+        .unwrap_or(false)
+}
+
+/// `*.json` files directly inside `dir`, sorted by path
+fn thread_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_thread(dir: &Path, id: &str) {
+        std::fs::write(
+            dir.join(format!("{}.json", id)),
+            format!(r#"{{"id": "{}", "messages": []}}"#, id),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_config_wins_over_env_and_defaults() {
+        let configured = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        std::fs::create_dir(home.path().join("threads")).unwrap();
+
+        let (path, source) = resolve_from(
+            Some(configured.path()),
+            Some(home.path().into()),
+            &[home.path().join("threads")],
+        )
+        .unwrap();
+        assert_eq!(path, configured.path());
+        assert_eq!(source, Source::Config);
+    }
+
+    #[test]
+    fn test_env_data_home() {
+        let home = tempfile::tempdir().unwrap();
+        std::fs::create_dir(home.path().join("threads")).unwrap();
+
+        let (path, source) = resolve_from(None, Some(home.path().into()), &[]).unwrap();
+        assert_eq!(path, home.path().join("threads"));
+        assert_eq!(source, Source::Env);
+    }
+
+    #[test]
+    fn test_defaults_tried_in_order() {
+        let missing = PathBuf::from("/nonexistent/amp/threads");
+        let present = tempfile::tempdir().unwrap();
+
+        let (path, source) =
+            resolve_from(None, None, &[missing, present.path().to_path_buf()]).unwrap();
+        assert_eq!(path, present.path());
+        assert_eq!(source, Source::PlatformDefault);
+    }
+
+    #[test]
+    fn test_explicit_dir_must_be_valid() {
+        let err = resolve_from(Some(Path::new("/nonexistent/threads")), None, &[]).unwrap_err();
+        assert_eq!(err.category(), "config");
+        assert!(err.to_string().contains("threads_dir"));
+
+        // An explicit choice doesn't fall back to defaults
+        let fallback = tempfile::tempdir().unwrap();
+        let result = resolve_from(
+            Some(Path::new("/nonexistent/threads")),
+            None,
+            &[fallback.path().to_path_buf()],
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_nothing_found_lists_tried_paths() {
+        let err = resolve_from(None, None, &[PathBuf::from("/nonexistent/a")]).unwrap_err();
+        assert!(err.to_string().contains("/nonexistent/a (not a directory)"));
+        assert!(err.to_string().contains(DATA_HOME_ENV));
+    }
+
+    #[test]
+    fn test_store_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(validate(dir.path()).is_ok());
+
+        write_thread(dir.path(), "T-5928a90d-d53b-488f-a829-4e36442142ee");
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+        assert!(validate(dir.path()).is_ok());
+
+        std::fs::write(dir.path().join("a-settings.json"), r#"{"theme": "dark"}"#).unwrap();
+        let reason = validate(dir.path()).unwrap_err();
+        assert!(reason.contains("a-settings.json"));
+    }
+
+    #[test]
+    fn test_thread_files_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        write_thread(dir.path(), "T-1");
+        write_thread(dir.path(), "T-2");
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let files = thread_files(dir.path()).unwrap();
+        assert_eq!(files.len(), 2);
+    }
+}
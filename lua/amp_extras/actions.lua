@@ -0,0 +1,49 @@
+-- Command palette data source (see crates/core/src/commands/actions.rs)
+local M = {}
+
+local ffi = require("amp_extras.ffi")
+
+---@class AmpExtras.Action
+---@field id string
+---@field title string
+---@field description string
+---@field category string
+---@field needs_selection boolean
+---@field needs_client boolean
+---@field invoke { kind: "ex"|"core", command: string, args?: table }
+---@field available boolean Requirements met in the current editor state
+---@field keymap? string Applied keymap, if any
+
+--- List palette actions with live availability and keymap hints
+---@return AmpExtras.Action[]
+function M.list()
+  local mode = vim.api.nvim_get_mode().mode
+  local status = require("amp_extras.lualine").get_status()
+
+  local result = ffi.call("actions.list", {
+    selection_active = mode:match("^[vV\22]") ~= nil,
+    client_connected = status.client_count > 0,
+  })
+  if type(result) ~= "table" or not result.actions then
+    return {}
+  end
+
+  local keymaps = require("amp_extras").keymaps or {}
+  for _, action in ipairs(result.actions) do
+    action.keymap = keymaps[action.id]
+  end
+  return result.actions
+end
+
+--- Run a palette action
+---@param action AmpExtras.Action
+---@return any result Core command result (core actions only)
+function M.run(action)
+  if action.invoke.kind == "ex" then
+    vim.cmd(action.invoke.command)
+    return nil
+  end
+  return ffi.call(action.invoke.command, action.invoke.args or {})
+end
+
+return M
@@ -0,0 +1,144 @@
+-- Compose buffer: write long messages in a markdown scratch buffer and send on :w
+local M = {}
+
+local lint = require("amp_extras.lint")
+local mentions = require("amp_extras.mentions")
+
+local BUF_NAME = "amp://compose"
+
+-- Compose buffer for this session (content persists until Neovim exits)
+local state = {
+  bufnr = nil,
+}
+
+local function get_amp_message()
+  local ok, amp_message = pcall(require, "amp.message")
+  if not ok then
+    vim.notify(
+      "amp-extras.nvim: sourcegraph/amp.nvim (module 'amp.message') not found.",
+      vim.log.levels.ERROR,
+      { title = "Amp Extras" }
+    )
+    return nil
+  end
+  return amp_message
+end
+
+--- Existing compose buffer, e.g. one created before `:AmpReload`
+---@return integer|nil bufnr
+local function find_buffer()
+  for _, bufnr in ipairs(vim.api.nvim_list_bufs()) do
+    if vim.api.nvim_buf_get_name(bufnr) == BUF_NAME then
+      return bufnr
+    end
+  end
+  return nil
+end
+
+--- Send the compose buffer's content
+---
+--- Runs the same mention expansion and lint checks as the message box.
+--- Submits the message, or appends it to the prompt when `opts.append` is
+--- set (`:w!` / `:AmpComposeSend!`).
+---@param opts? { append?: boolean }
+---@return boolean sent
+function M.send(opts)
+  opts = opts or {}
+  local bufnr = state.bufnr
+  if not bufnr or not vim.api.nvim_buf_is_valid(bufnr) then
+    bufnr = find_buffer()
+  end
+  if not bufnr then
+    vim.notify("No compose buffer; open one with :AmpCompose", vim.log.levels.WARN, { title = "Amp Extras" })
+    return false
+  end
+
+  local text = table.concat(vim.api.nvim_buf_get_lines(bufnr, 0, -1, false), "\n")
+  if vim.trim(text) == "" then
+    vim.notify("Compose buffer is empty", vim.log.levels.WARN, { title = "Amp Extras" })
+    return false
+  end
+
+  local amp_message = get_amp_message()
+  if not amp_message then
+    return false
+  end
+
+  text = mentions.expand(text)
+  if not lint.check(text) then
+    return false
+  end
+
+  if opts.append then
+    amp_message.send_to_prompt(text)
+  else
+    amp_message.send_message(text)
+  end
+
+  vim.bo[bufnr].modified = false
+  vim.notify(opts.append and "Appended to Amp prompt" or "Sent to Amp", vim.log.levels.INFO, { title = "Amp Extras" })
+  return true
+end
+
+--- Set up the compose buffer's options and autocmds
+---
+--- The autocmds live in an augroup cleared on every call, so a buffer left
+--- over from before `:AmpReload` is rewired to this copy of the module.
+---@param bufnr integer
+local function setup_buffer(bufnr)
+  -- acwrite: :w runs BufWriteCmd instead of writing a file
+  vim.bo[bufnr].buftype = "acwrite"
+  vim.bo[bufnr].bufhidden = "hide"
+  vim.bo[bufnr].swapfile = false
+  vim.bo[bufnr].filetype = "markdown"
+
+  local group = vim.api.nvim_create_augroup("AmpExtrasCompose", { clear = true })
+  vim.api.nvim_create_autocmd("BufWriteCmd", {
+    group = group,
+    buffer = bufnr,
+    desc = "amp-extras: send compose buffer",
+    callback = function()
+      M.send({ append = vim.v.cmdbang == 1 })
+    end,
+  })
+  vim.api.nvim_create_autocmd("BufWipeout", {
+    group = group,
+    buffer = bufnr,
+    callback = function()
+      state.bufnr = nil
+    end,
+  })
+end
+
+--- Compose buffer for this session, reusing an existing one
+---@return integer bufnr
+local function get_buffer()
+  local bufnr = state.bufnr
+  if bufnr and vim.api.nvim_buf_is_valid(bufnr) then
+    return bufnr
+  end
+
+  bufnr = find_buffer()
+  if not bufnr then
+    bufnr = vim.api.nvim_create_buf(true, false)
+    vim.api.nvim_buf_set_name(bufnr, BUF_NAME)
+  end
+  setup_buffer(bufnr)
+  state.bufnr = bufnr
+  return bufnr
+end
+
+--- Open the compose buffer, focusing it if it's already shown
+function M.open()
+  local bufnr = get_buffer()
+  local winid = vim.fn.bufwinid(bufnr)
+  if winid ~= -1 then
+    vim.api.nvim_set_current_win(winid)
+    return
+  end
+
+  vim.cmd("botright split")
+  vim.api.nvim_win_set_buf(0, bufnr)
+end
+
+return M
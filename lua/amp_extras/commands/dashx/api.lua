@@ -23,6 +23,51 @@ function M.list_prompts()
   return result.prompts
 end
 
+---@class PromptPage
+---@field items Prompt[]
+---@field next_cursor string?
+
+---List one page of prompts
+---@param cursor string? Opaque cursor from a previous page's `next_cursor`
+---@param page_size number? Items per page (default 50)
+---@return PromptPage
+function M.list_prompts_page(cursor, page_size)
+  local result = ffi.call("prompts.list", { cursor = cursor, page_size = page_size or 50 })
+  if result.error then
+    error(result.message)
+  end
+  return result
+end
+
+---Full-text search over prompts (best matches first)
+---@param query string
+---@param limit number?
+---@return Prompt[]
+function M.search_prompts(query, limit)
+  local result = ffi.call("prompts.search", { query = query, limit = limit })
+  if result.error then
+    error(result.message)
+  end
+  return result.prompts
+end
+
+---@class PromptImportSummary
+---@field imported number
+---@field skipped number
+---@field updated number
+---@field failed { path: string, reason: string }[] Files that couldn't be read
+
+---Import `.md`/`.txt` files from a directory as prompts
+---@param path string Directory to scan (non-recursive)
+---@return PromptImportSummary
+function M.import_prompts(path)
+  local result = ffi.call("prompts.import_from_directory", { path = vim.fn.expand(path) })
+  if result.error then
+    error(result.message)
+  end
+  return result
+end
+
 ---Create a new prompt
 ---@param title string
 ---@param description string?
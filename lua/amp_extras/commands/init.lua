@@ -33,12 +33,31 @@ function M.register_commands()
     desc = "Amp: Update Amp CLI",
   })
 
+  vim.api.nvim_create_user_command("AmpReload", function()
+    require("amp_extras").reload()
+  end, {
+    desc = "Amp: Reload amp-extras Lua modules and core state (development)",
+  })
+
   vim.api.nvim_create_user_command("AmpSendMessage", function()
     M.ui.send_message_box.command()
   end, {
     desc = "Amp: Open send message UI",
   })
 
+  vim.api.nvim_create_user_command("AmpCompose", function()
+    require("amp_extras.commands.compose").open()
+  end, {
+    desc = "Amp: Compose a message in a buffer (:w sends, :w! appends to prompt)",
+  })
+
+  vim.api.nvim_create_user_command("AmpComposeSend", function(cmd_opts)
+    require("amp_extras.commands.compose").send({ append = cmd_opts.bang })
+  end, {
+    bang = true,
+    desc = "Amp: Send the compose buffer (bang appends to prompt)",
+  })
+
   -- Send commands (Lua, through sourcegraph/amp.nvim)
   vim.api.nvim_create_user_command("AmpSendFileRef", function()
     send.send_file_ref()
@@ -52,9 +71,10 @@ function M.register_commands()
     desc = "Amp: Send current line reference to Amp prompt (@file.rs#L10)",
   })
 
-  vim.api.nvim_create_user_command("AmpSendBuffer", function()
-    send.send_buffer()
+  vim.api.nvim_create_user_command("AmpSendBuffer", function(cmd_opts)
+    send.send_buffer(cmd_opts)
   end, {
+    bang = true,
     desc = "Amp: Send entire buffer content to Amp prompt",
   })
 
@@ -62,6 +82,7 @@ function M.register_commands()
     send.send_selection(cmd_opts)
   end, {
     range = true,
+    bang = true,
     desc = "Amp: Send selected text to Amp prompt",
   })
 
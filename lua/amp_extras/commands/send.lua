@@ -1,5 +1,50 @@
 local M = {}
 
+local ffi = require("amp_extras.ffi")
+local lint = require("amp_extras.lint")
+
+--- Render an outgoing message template (see crates/core/src/format.rs)
+---
+--- Falls back to `fallback` when the core library is unavailable so sends
+--- keep working without it.
+---@param template string Template name ("selection", "file_ref", "diagnostic")
+---@param values table Placeholder values
+---@param fallback string Text to send if rendering fails
+---@return string
+local function render(template, values, fallback)
+  if not ffi.is_available() then
+    return fallback
+  end
+
+  local result = ffi.call("format.render", { template = template, values = values })
+  if type(result) ~= "table" or type(result.text) ~= "string" then
+    return fallback
+  end
+  return result.text
+end
+
+--- Join buffer lines for a content send
+---
+--- By default (`line_endings = "lf"`) lines are joined with `\n` and stray
+--- `\r`s from mixed line endings are dropped. With `line_endings =
+--- "original"`, lines are joined with the buffer's own line ending
+--- (`fileformat`), so CRLF files send CRLF content.
+---@param lines string[]
+---@return string
+local function join_lines(lines)
+  local config = require("amp_extras").config or {}
+  if config.line_endings == "original" then
+    local eol = ({ dos = "\r\n", mac = "\r" })[vim.bo.fileformat] or "\n"
+    return table.concat(lines, eol)
+  end
+
+  local normalized = {}
+  for i, line in ipairs(lines) do
+    normalized[i] = line:gsub("\r$", "")
+  end
+  return table.concat(normalized, "\n")
+end
+
 local function get_amp_message()
   local ok, amp_message = pcall(require, "amp.message")
   if not ok then
@@ -14,6 +59,23 @@ local function get_amp_message()
   return amp_message
 end
 
+--- Path to use in an `@path` reference for a buffer name
+---
+--- Relative to the cwd when the file is inside it. Files outside the cwd get
+--- their absolute path (`:.` would leave `~/...`, which agents can't
+--- resolve), with `absolute` set so callers can tell.
+---@param bufname string
+---@return string path
+---@return boolean absolute
+function M.ref_path(bufname)
+  local full = vim.fn.fnamemodify(bufname, ":p")
+  local relative = vim.fn.fnamemodify(full, ":.")
+  if relative == full or relative:sub(1, 1) == "~" then
+    return full, true
+  end
+  return relative, false
+end
+
 function M.send_file_ref()
   local amp_message = get_amp_message()
   if not amp_message then
@@ -26,10 +88,12 @@ function M.send_file_ref()
     return
   end
 
-  local relative_path = vim.fn.fnamemodify(bufname, ":.")
-  local ref = "@" .. relative_path
+  local path, absolute = M.ref_path(bufname)
+  local ref = "@" .. path
 
-  amp_message.send_to_prompt(ref)
+  amp_message.send_to_prompt(
+    render("file_ref", { path = path, range = "", absolute = absolute }, ref)
+  )
 end
 
 function M.send_line_ref()
@@ -44,14 +108,21 @@ function M.send_line_ref()
     return
   end
 
-  local relative_path = vim.fn.fnamemodify(bufname, ":.")
+  local path, absolute = M.ref_path(bufname)
   local line = vim.api.nvim_win_get_cursor(0)[1]
-  local ref = string.format("@%s#L%d", relative_path, line)
+  local range = string.format("#L%d", line)
+  local ref = "@" .. path .. range
 
-  amp_message.send_to_prompt(ref)
+  amp_message.send_to_prompt(render("file_ref", {
+    path = path,
+    start_line = line,
+    end_line = line,
+    range = range,
+    absolute = absolute,
+  }, ref))
 end
 
-function M.send_buffer()
+function M.send_buffer(cmd_opts)
   local amp_message = get_amp_message()
   if not amp_message then
     return
@@ -59,7 +130,10 @@ function M.send_buffer()
 
   local buf = vim.api.nvim_get_current_buf()
   local lines = vim.api.nvim_buf_get_lines(buf, 0, -1, false)
-  local content = table.concat(lines, "\n")
+  local content = join_lines(lines)
+  if not lint.check(content, { allow_secrets = cmd_opts and cmd_opts.bang }) then
+    return
+  end
 
   amp_message.send_to_prompt(content)
 end
@@ -70,24 +144,82 @@ function M.send_selection(cmd_opts)
     return
   end
 
+  local values = {
+    path = (M.ref_path(vim.api.nvim_buf_get_name(0))),
+    filetype = vim.bo.filetype,
+  }
+
   -- Use marks for exact visual selection (character-accurate)
   local start_pos = vim.api.nvim_buf_get_mark(0, "<")
   local end_pos = vim.api.nvim_buf_get_mark(0, ">")
   if start_pos[1] == 0 or end_pos[1] == 0 then
     -- Fallback: line range from user command
     local lines = vim.api.nvim_buf_get_lines(0, cmd_opts.line1 - 1, cmd_opts.line2, false)
-    local text = table.concat(lines, "\n")
-    amp_message.send_to_prompt(text)
+    values.content = join_lines(lines)
+    values.start_line, values.end_line = cmd_opts.line1, cmd_opts.line2
+    local text = render("selection", values, values.content)
+    if lint.check(text, { allow_secrets = cmd_opts.bang }) then
+      amp_message.send_to_prompt(text)
+    end
     return
   end
 
   local lines =
     vim.api.nvim_buf_get_text(0, start_pos[1] - 1, start_pos[2], end_pos[1] - 1, end_pos[2] + 1, {})
-  local text = table.concat(lines, "\n")
-  amp_message.send_to_prompt(text)
+  values.content = join_lines(lines)
+  values.start_line, values.end_line = start_pos[1], end_pos[1]
+  local text = render("selection", values, values.content)
+  if lint.check(text, { allow_secrets = cmd_opts.bang }) then
+    amp_message.send_to_prompt(text)
+  end
+end
+
+--- `#L..` suffix for a selection reference
+---
+--- Line-only by default (`#L10-20`, `#L10`, or "" for line 1). With both
+--- columns given, positions are `line:col` (1-based, inclusive):
+--- `#L10:5-L20:12`.
+---@param line1 integer
+---@param line2 integer
+---@param start_col? integer
+---@param end_col? integer
+---@return string
+function M.format_range(line1, line2, start_col, end_col)
+  if start_col and end_col then
+    return string.format("#L%d:%d-L%d:%d", line1, start_col, line2, end_col)
+  end
+  if line1 ~= line2 then
+    return "#L" .. line1 .. "-" .. line2
+  elseif line1 > 1 then
+    return "#L" .. line1
+  end
+  return ""
+end
+
+--- Character columns of the last charwise visual selection covering
+--- `line1`..`line2`, or nil (linewise/blockwise selection, or a range that
+--- didn't come from the selection)
+---@return integer? start_col
+---@return integer? end_col
+local function selection_columns(line1, line2)
+  local start_pos = vim.api.nvim_buf_get_mark(0, "<")
+  local end_pos = vim.api.nvim_buf_get_mark(0, ">")
+  if vim.fn.visualmode() ~= "v" or start_pos[1] ~= line1 or end_pos[1] ~= line2 then
+    return nil
+  end
+
+  local start_text = vim.api.nvim_buf_get_lines(0, line1 - 1, line1, false)[1] or ""
+  local end_text = vim.api.nvim_buf_get_lines(0, line2 - 1, line2, false)[1] or ""
+  -- Mark columns are 0-based bytes; '> can sit past the end of the line
+  local end_byte = math.max(math.min(end_pos[2], #end_text - 1), 0)
+  return vim.fn.charidx(start_text, start_pos[2]) + 1, vim.fn.charidx(end_text, end_byte) + 1
 end
 
-function M.send_selection_ref(cmd_opts)
+--- Send a reference to the selected lines
+---@param cmd_opts table User command opts (line1, line2)
+---@param opts? { columns?: boolean } Include columns (default: config.selection_ref_columns)
+function M.send_selection_ref(cmd_opts, opts)
+  opts = opts or {}
   local amp_message = get_amp_message()
   if not amp_message then
     return
@@ -99,17 +231,148 @@ function M.send_selection_ref(cmd_opts)
     return
   end
 
-  local relative_path = vim.fn.fnamemodify(bufname, ":.")
-  local ref = "@" .. relative_path
+  local path, absolute = M.ref_path(bufname)
   local line1, line2 = cmd_opts.line1, cmd_opts.line2
 
-  if line1 ~= line2 then
-    ref = ref .. "#L" .. line1 .. "-" .. line2
-  elseif line1 > 1 then
-    ref = ref .. "#L" .. line1
+  local columns = opts.columns
+  if columns == nil then
+    columns = (require("amp_extras").config or {}).selection_ref_columns
+  end
+  local start_col, end_col
+  if columns then
+    start_col, end_col = selection_columns(line1, line2)
+  end
+  local range = M.format_range(line1, line2, start_col, end_col)
+
+  local ref = "@" .. path .. range
+  amp_message.send_to_prompt(render("file_ref", {
+    path = path,
+    start_line = line1,
+    end_line = line2,
+    range = range,
+    absolute = absolute,
+  }, ref))
+end
+
+--- Lines yanked by a TextYankPost event
+---
+--- `regcontents` is the whole register, so for appending yanks (`"Ayy`)
+--- only the newly yanked part is taken: read back from the buffer for
+--- linewise and charwise yanks, the last lines of the register for blockwise.
+---@param event table TextYankPost event
+---@param start_pos integer[] '[ mark
+---@param end_pos integer[] '] mark
+---@return string[]
+local function yanked_lines(event, start_pos, end_pos)
+  local lines = event.regcontents or {}
+  if not (event.regname or ""):match("^%u$") then
+    return lines
+  end
+
+  if event.regtype == "V" then
+    return vim.api.nvim_buf_get_lines(0, start_pos[1] - 1, end_pos[1], false)
+  elseif event.regtype == "v" then
+    local last = vim.api.nvim_buf_get_lines(0, end_pos[1] - 1, end_pos[1], false)[1] or ""
+    local end_col = math.min(end_pos[2] + 1, #last)
+    return vim.api.nvim_buf_get_text(0, start_pos[1] - 1, start_pos[2], end_pos[1] - 1, end_col, {})
+  end
+  return vim.list_slice(lines, #lines - (end_pos[1] - start_pos[1]))
+end
+
+--- Send yanked text to the Amp prompt (send_on_yank)
+---
+--- Called from TextYankPost with `vim.v.event`. Sends a file reference for
+--- the yanked lines followed by the text, through the file_ref and
+--- selection templates and the lint checks. The text goes in a code fence
+--- unless the selection template changes it (and so adds its own).
+--- Linewise and blockwise yanks keep their lines as-is; charwise yanks send
+--- exactly the yanked characters.
+---@param event table TextYankPost event (operator, regname, regtype, regcontents)
+---@param opts? { register?: string } Only send yanks into this register ("" or nil: any);
+--- appending yanks (`"Ayy`) count for their lowercase register
+---@return boolean sent
+function M.send_yank(event, opts)
+  opts = opts or {}
+  if event.operator ~= "y" then
+    return false
+  end
+  local register = (opts.register or ""):lower()
+  if register ~= "" and (event.regname or ""):lower() ~= register then
+    return false
+  end
+
+  local bufname = vim.api.nvim_buf_get_name(0)
+  if bufname == "" or vim.bo.buftype ~= "" then
+    return false
+  end
+
+  local amp_message = get_amp_message()
+  if not amp_message then
+    return false
+  end
+
+  -- '[ and '] mark the yanked region
+  local start_pos = vim.api.nvim_buf_get_mark(0, "[")
+  local end_pos = vim.api.nvim_buf_get_mark(0, "]")
+  local line1, line2 = start_pos[1], end_pos[1]
+
+  local content = join_lines(yanked_lines(event, start_pos, end_pos))
+  if content == "" then
+    return false
+  end
+
+  local path, absolute = M.ref_path(bufname)
+  local range = line1 == line2 and ("#L" .. line1) or ("#L" .. line1 .. "-" .. line2)
+
+  local ref = render("file_ref", {
+    path = path,
+    start_line = line1,
+    end_line = line2,
+    range = range,
+    absolute = absolute,
+  }, "@" .. path .. range)
+
+  local fenced = "```" .. vim.bo.filetype .. "\n" .. content .. "\n```"
+  local body = render("selection", {
+    path = path,
+    filetype = vim.bo.filetype,
+    content = content,
+    start_line = line1,
+    end_line = line2,
+  }, fenced)
+  -- The default selection template sends the text as-is; fence it then,
+  -- but leave templates that add their own fence alone
+  if body == content then
+    body = fenced
+  end
+  local text = ref .. "\n" .. body
+
+  if not lint.check(text) then
+    return false
+  end
+  amp_message.send_to_prompt(text)
+  return true
+end
+
+--- Register (or clear) the send-on-yank autocmd
+---
+--- The augroup is cleared every time, so calling this again with
+--- `enabled = false` (e.g. setup on reload) removes the autocmd.
+---@param opts? { enabled?: boolean, register?: string } `config.send_on_yank`
+function M.setup_send_on_yank(opts)
+  opts = opts or {}
+  local group = vim.api.nvim_create_augroup("AmpExtrasSendOnYank", { clear = true })
+  if not opts.enabled then
+    return
   end
 
-  amp_message.send_to_prompt(ref)
+  vim.api.nvim_create_autocmd("TextYankPost", {
+    group = group,
+    desc = "amp-extras: send yanked text to Amp",
+    callback = function()
+      M.send_yank(vim.v.event, { register = opts.register })
+    end,
+  })
 end
 
 return M
@@ -132,7 +132,12 @@ M.command = function()
         end
       end,
       on_submit = function(value)
-        -- Send the value as-is (preserves newlines from Shift+Enter)
+        -- Send the value as typed (preserves newlines from Shift+Enter),
+        -- with @prompt: mentions expanded
+        value = require("amp_extras.mentions").expand(value)
+        if not require("amp_extras.lint").check(value) then
+          return
+        end
         local ok, amp_message = pcall(require, "amp.message")
         if ok then
           amp_message.send_message(value)
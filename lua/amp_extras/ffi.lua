@@ -33,7 +33,7 @@ end
 -- ============================================================================
 
 --- Call a command through the FFI
----@param command string Command name (e.g., "ping", "send_selection")
+---@param command AmpExtras.Command|string Command name (see docs/commands.md)
 ---@param args table Command arguments
 ---@return table Result or error object
 function M.call(command, args)
@@ -45,9 +45,26 @@ function M.call(command, args)
   args = args or {}
   local result = mod.call(command, args)
 
+  -- Surface non-fatal warnings (e.g. deprecated command names)
+  if mod.take_warnings then
+    for _, warning in ipairs(mod.take_warnings()) do
+      vim.notify("amp-extras: " .. warning, vim.log.levels.WARN)
+    end
+  end
+
   -- Check if result is an error
+  -- (error/message keep `if result.error then error(result.message) end`
+  -- working; code/data/category let callers branch on the error type)
   if result.error then
-    return { nil, result.message }
+    return {
+      nil,
+      result.message,
+      error = true,
+      message = result.message,
+      code = result.code,
+      data = result.data,
+      category = result.category,
+    }
   end
 
   return result
@@ -0,0 +1,54 @@
+-- :checkhealth amp_extras
+local M = {}
+
+local health = vim.health
+
+function M.check()
+  health.start("amp-extras: core library")
+
+  local ffi = require("amp_extras.ffi")
+  if not ffi.is_available() then
+    health.error("Core library not loaded: " .. (ffi.get_error() or "unknown error"), {
+      "Run `just build` or reinstall the plugin to download a prebuilt binary",
+    })
+    return
+  end
+  health.ok("Core library loaded")
+
+  health.start("amp-extras: integrations")
+
+  if pcall(require, "amp.message") then
+    health.ok("sourcegraph/amp.nvim found")
+  else
+    health.error("sourcegraph/amp.nvim (module 'amp.message') not found", {
+      "Install https://github.com/sourcegraph/amp.nvim and run require('amp').setup(...)",
+    })
+  end
+
+  local result = ffi.call("health", {})
+  if type(result.checks) ~= "table" then
+    health.error("Health checks failed: " .. tostring(result.message or result[2]))
+    return
+  end
+
+  health.start("amp-extras: environment")
+  for _, check in ipairs(result.checks) do
+    local line = check.name .. ": " .. check.detail
+    if check.ok then
+      health.ok(line)
+    elseif check.name == "amp_cli" then
+      -- Sends go through amp.nvim; the CLI is only needed for login/update/sessions
+      health.warn(line, { "Install the Amp CLI: https://ampcode.com" })
+    else
+      health.error(line)
+    end
+  end
+
+  local resolved = require("amp_extras").resolved_config
+  if resolved then
+    health.start("amp-extras: resolved configuration")
+    health.info(vim.inspect(resolved))
+  end
+end
+
+return M
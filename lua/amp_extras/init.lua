@@ -15,6 +15,7 @@ M.autocomplete = ffi.autocomplete
 local defaults = {
   lazy = false, -- Lazy load the plugin
   prefix = "a", -- Default prefix (mappings will be <leader> + prefix)
+  warmup_db = false, -- Open the prompts database right after startup instead of on first use
 
   -- Feature flags: Toggle groups of functionality
   features = {
@@ -27,6 +28,45 @@ local defaults = {
     lualine = true, -- Lualine integration
   },
 
+  -- Outgoing message templates (nil = built-in default)
+  -- Placeholders: {{path}}, {{start_line}}, {{end_line}}, {{content}},
+  -- {{filetype}}, {{severity}}, {{message}}, {{range}},
+  -- {{absolute}} (file_ref: true when the file is outside the cwd)
+  -- e.g. selection = "Context from {{path}}:\n```{{filetype}}\n{{content}}\n```"
+  templates = {},
+
+  -- Pre-send checks for selection/buffer/message sends
+  -- Secrets (AWS keys, private keys, API tokens) block the send unless
+  -- allow_secrets is set or the command is run with a bang (:AmpSendSelection!)
+  lint = {
+    max_bytes = 1024 * 1024, -- Hard limit on sent text size
+    allow_secrets = false,
+    patterns = {}, -- Extra secret patterns: { name = "regex" }
+  },
+
+  -- Amp CLI thread directory (nil = $AMP_DATA_HOME/threads or the platform
+  -- default, e.g. ~/.local/share/amp/threads); check with the threads.storage_info command
+  threads_dir = nil,
+
+  -- Append yanked text (with a file reference) to the Amp prompt
+  -- register = "" sends yanks into any register
+  send_on_yank = {
+    enabled = false,
+    register = "a",
+  },
+
+  -- Line endings in sent buffer/selection content: "lf" always sends \n,
+  -- "original" keeps the buffer's fileformat (CRLF files send \r\n)
+  line_endings = "lf",
+
+  -- Include columns in selection references for charwise selections
+  -- (@file.rs#L10:5-L20:12 instead of @file.rs#L10-20)
+  selection_ref_columns = false,
+
+  -- Expand @prompt:<title-or-id> mentions in typed messages before sending
+  -- (@@prompt: sends the literal text)
+  expand_mentions = true,
+
   -- Keymap overrides
   -- Map action name to specific key string (e.g., send_selection = "<leader>x")
   -- or set to false to disable specific keymap even if feature is enabled
@@ -84,6 +124,13 @@ local actions = {
     cmd = "<cmd>AmpSendMessage<cr>",
     desc = "Send Message UI",
   },
+  compose = {
+    feature = "message",
+    suffix = "sc",
+    mode = "n",
+    cmd = "<cmd>AmpCompose<cr>",
+    desc = "Compose Message Buffer",
+  },
 
   -- Login/Account
   login = {
@@ -149,6 +196,12 @@ M.lualine = require("amp_extras.lualine")
 -- Active configuration (merged defaults + user config)
 M.config = vim.deepcopy(defaults)
 
+-- Effective core configuration reported by setup (nil until setup runs)
+M.resolved_config = nil
+
+-- Applied keymaps by action name (for keymap hints in amp_extras.actions)
+M.keymaps = {}
+
 -- ============================================================================
 -- Setup & Configuration
 -- ============================================================================
@@ -159,6 +212,7 @@ local function setup_keymaps(config)
   local prefix = "<leader>" .. (config.prefix or "a")
   local user_keymaps = config.keymaps or {}
   local features = config.features or {}
+  M.keymaps = {}
 
   for action_name, def in pairs(actions) do
     -- Check if feature is enabled
@@ -185,6 +239,7 @@ local function setup_keymaps(config)
           silent = true,
           desc = def.desc,
         })
+        M.keymaps[action_name] = lhs
       end
     end
   end
@@ -208,19 +263,42 @@ function M.setup(opts)
   end
 
   M.config = vim.tbl_deep_extend("force", defaults, opts)
+  M.user_opts = opts
 
   -- Call Rust FFI setup
-  local setup_result = ffi.setup({})
+  local setup_result = ffi.setup({
+    templates = M.config.templates,
+    lint = M.config.lint,
+    threads_dir = M.config.threads_dir and vim.fn.expand(M.config.threads_dir) or nil,
+  })
   if setup_result and setup_result.error then
     vim.notify(
       "amp-extras: FFI setup failed: " .. (setup_result.message or "unknown error"),
       vim.log.levels.ERROR
     )
+  elseif setup_result then
+    -- Effective config as applied by the core (templates, lint, db path)
+    M.resolved_config = vim.tbl_extend("force", setup_result.config or {}, { db = setup_result.db })
+
+    -- Schema repairs on an already-open database (later ones arrive via ffi.call)
+    for _, warning in ipairs(setup_result.db and setup_result.db.warnings or {}) do
+      vim.notify("amp-extras: " .. warning, vim.log.levels.WARN)
+    end
+  end
+
+  -- Open the database once the UI is up (setup itself never touches disk)
+  if M.config.warmup_db then
+    vim.defer_fn(function()
+      ffi.call("db.warmup", {})
+    end, 100)
   end
 
   -- Setup keymaps
   setup_keymaps(M.config)
 
+  -- Send-on-yank
+  require("amp_extras.commands.send").setup_send_on_yank(M.config.send_on_yank)
+
   -- Register UI commands
   M.register_ui_commands()
 
@@ -232,6 +310,36 @@ function M.setup(opts)
   return M.config
 end
 
+--- Reload amp-extras in place (plugin development, :AmpReload)
+---
+--- Releases core state via `plugin.reload_prepare` (database pool, caches),
+--- unloads the amp_extras Lua modules and runs setup again with the same
+--- options. Augroups are created with `clear = true`, so running setup again
+--- doesn't duplicate autocmds. The core library itself stays loaded: changes
+--- to the Rust code still need a Neovim restart.
+---@return table report Result of `plugin.reload_prepare`
+function M.reload()
+  local report = ffi.call("plugin.reload_prepare", {})
+  local opts = M.user_opts
+
+  for name in pairs(package.loaded) do
+    if name == "amp_extras" or name:match("^amp_extras%.") then
+      package.loaded[name] = nil
+    end
+  end
+
+  require("amp_extras").setup(opts)
+
+  local restart = report.requires_restart or {}
+  vim.notify(
+    "amp-extras reloaded"
+      .. (#restart > 0 and (" (restart Neovim to reload: " .. table.concat(restart, ", ") .. ")") or ""),
+    vim.log.levels.INFO,
+    { title = "Amp Extras" }
+  )
+  return report
+end
+
 --- Register UI commands (can also be called manually)
 function M.register_ui_commands()
   local commands = require("amp_extras.commands")
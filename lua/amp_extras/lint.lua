@@ -0,0 +1,34 @@
+-- Pre-send checks for outgoing prompt text (see crates/core/src/lint.rs)
+local M = {}
+
+local ffi = require("amp_extras.ffi")
+
+--- Check text before sending it to Amp
+---
+--- Notifies and returns false when the text is over the size limit or looks
+--- like it contains a secret. Sends are allowed when the core library is
+--- unavailable so they keep working without it.
+---@param text string Text about to be sent
+---@param opts? { allow_secrets?: boolean }
+---@return boolean ok
+function M.check(text, opts)
+  if not ffi.is_available() then
+    return true
+  end
+
+  opts = opts or {}
+  local result = ffi.call("lint.check", { text = text, allow_secrets = opts.allow_secrets })
+  if type(result) == "table" and result.ok then
+    return true
+  end
+
+  local message = type(result) == "table" and (result.message or result[2]) or nil
+  vim.notify(
+    "Not sent: " .. (message or "lint check failed"),
+    vim.log.levels.ERROR,
+    { title = "Amp Extras" }
+  )
+  return false
+end
+
+return M
@@ -32,6 +32,9 @@ local function get_amp_status()
   }
 end
 
+-- Exposed for the command palette (amp_extras.actions)
+M.get_status = get_amp_status
+
 --- Setup lualine integration
 --- @param config table|nil Configuration options
 function M.setup(config)
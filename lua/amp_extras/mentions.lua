@@ -0,0 +1,44 @@
+-- @prompt: mention expansion for outgoing messages (see crates/core/src/mentions.rs)
+local M = {}
+
+local ffi = require("amp_extras.ffi")
+
+--- Expand `@prompt:<title-or-id>` mentions in a message
+---
+--- Returns the text unchanged when expansion is disabled (`opts.expand =
+--- false` or `expand_mentions = false` in setup), the core library is
+--- unavailable, or the lookup fails. Mentions that don't match a prompt are
+--- left as written and reported once per send.
+---@param text string Message about to be sent
+---@param opts? { expand?: boolean }
+---@return string text
+function M.expand(text, opts)
+  opts = opts or {}
+  local config = require("amp_extras").config or {}
+  local enabled = opts.expand
+  if enabled == nil then
+    enabled = config.expand_mentions ~= false
+  end
+  if not enabled or not text:find("@prompt:", 1, true) or not ffi.is_available() then
+    return text
+  end
+
+  local result = ffi.call("prompts.expand_mentions", { text = text })
+  if type(result) ~= "table" or not result.text then
+    local message = type(result) == "table" and (result.message or result[2]) or nil
+    vim.notify("amp-extras: mention expansion failed: " .. (message or "unknown error"), vim.log.levels.WARN)
+    return text
+  end
+
+  local unresolved = {}
+  for _, mention in ipairs(result.unresolved or {}) do
+    table.insert(unresolved, "@prompt:" .. mention.mention .. " (" .. mention.reason .. ")")
+  end
+  if #unresolved > 0 then
+    vim.notify("amp-extras: unresolved mentions: " .. table.concat(unresolved, ", "), vim.log.levels.WARN)
+  end
+
+  return result.text
+end
+
+return M
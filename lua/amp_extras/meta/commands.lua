@@ -0,0 +1,165 @@
+---@meta
+-- Generated by `meta.generate`; do not edit. Regenerate with `just gen-docs`.
+
+---Command names accepted by `require("amp_extras.ffi").call`
+---@alias AmpExtras.Command
+---| "actions.list"
+---| "amp.cancel"
+---| "amp.result"
+---| "amp.run"
+---| "amp.version"
+---| "commands.list"
+---| "context.get"
+---| "context.set"
+---| "db.rebuild"
+---| "db.warmup"
+---| "format.preview"
+---| "format.render"
+---| "health"
+---| "lint.check"
+---| "meta.generate"
+---| "ping"
+---| "plugin.reload_prepare"
+---| "prompts.create"
+---| "prompts.delete"
+---| "prompts.expand_mentions"
+---| "prompts.import_from_directory"
+---| "prompts.list"
+---| "prompts.mark_used"
+---| "prompts.recent"
+---| "prompts.search"
+---| "prompts.update"
+---| "prompts.use"
+---| "threads.create"
+---| "threads.storage_info"
+
+---Arguments for `actions.list`: Curated user-facing actions for a command palette, with live availability.
+---@class AmpExtras.Args.ActionsList
+---@field selection_active? boolean A visual selection is active
+---@field client_connected? boolean An Amp CLI is connected to amp.nvim
+
+---Arguments for `amp.cancel`: Cancel an in-flight Amp CLI run, killing its process group.
+---@class AmpExtras.Args.AmpCancel
+---@field run_id integer Run id returned by `amp.run` (`runId` is accepted too)
+
+---Arguments for `amp.result`: Result of a background Amp CLI run, or `{ running = true, cancelling }`.
+---@class AmpExtras.Args.AmpResult
+---@field run_id integer Run id returned by `amp.run` (`runId` is accepted too)
+
+---Arguments for `amp.run`: Start the Amp CLI in the background; collect its output with `amp.result`.
+---@class AmpExtras.Args.AmpRun
+---@field args string[] CLI arguments (max 64, passed without a shell)
+---@field stdin? string Text written to the process's stdin
+---@field cwd? string Working directory
+---@field timeout_ms? integer Time limit (default 120000, max 600000)
+---@field wait? boolean Block until the CLI exits and return its output instead of `{ run_id }`
+
+---Arguments for `amp.version`: Amp CLI version from `amp --version`, cached for the session.
+---@class AmpExtras.Args.AmpVersion
+
+---Arguments for `commands.list`: List registered command names.
+---@class AmpExtras.Args.CommandsList
+---@field include_aliases? boolean Also list aliases, with `alias_of` and `deprecated`
+
+---Arguments for `context.get`: Active thread id for the session (`nil` when none is active).
+---@class AmpExtras.Args.ContextGet
+
+---Arguments for `context.set`: Set or clear the active thread id.
+---@class AmpExtras.Args.ContextSet
+---@field thread_id? string Thread id (`T-<uuid>`); omit or `nil` to clear
+
+---Arguments for `db.rebuild`: Move a damaged prompts database aside and start a new one.
+---@class AmpExtras.Args.DbRebuild
+
+---Arguments for `db.warmup`: Open and migrate the prompts database ahead of first use.
+---@class AmpExtras.Args.DbWarmup
+
+---Arguments for `format.preview`: Preview a template against sample values without failing on invalid templates.
+---@class AmpExtras.Args.FormatPreview
+---@field template_name string Template name: `selection`, `diagnostic` or `file_ref`
+---@field sample? table Placeholder values
+---@field template? string Template text to validate instead of the configured one
+
+---Arguments for `format.render`: Render a configured message template.
+---@class AmpExtras.Args.FormatRender
+---@field template string Template name: `selection`, `diagnostic` or `file_ref`
+---@field values? table Placeholder values
+
+---Arguments for `health`: Run diagnostic checks (database, Amp CLI) for `:checkhealth amp_extras`.
+---@class AmpExtras.Args.Health
+
+---Arguments for `lint.check`: Check outgoing text for oversize payloads and likely secrets.
+---@class AmpExtras.Args.LintCheck
+---@field text string Text about to be sent
+---@field allow_secrets? boolean Skip the secret scan for this call
+
+---Arguments for `meta.generate`: Generate the command reference (Markdown) and Lua annotations.
+---@class AmpExtras.Args.MetaGenerate
+---@field markdown_path? string Write the Markdown reference here instead of returning it
+---@field lua_path? string Write the Lua annotations here instead of returning them
+
+---Arguments for `ping`: Echo the arguments back with `pong = true`.
+---@class AmpExtras.Args.Ping
+
+---Arguments for `plugin.reload_prepare`: Release re-initializable state (database pool, caches) before `:AmpReload`.
+---@class AmpExtras.Args.PluginReloadPrepare
+
+---Arguments for `prompts.create`: Create a prompt.
+---@class AmpExtras.Args.PromptsCreate
+---@field title string Display title
+---@field description? string Optional description
+---@field content string Prompt text
+---@field tags? string[] Tags
+
+---Arguments for `prompts.delete`: Delete a prompt.
+---@class AmpExtras.Args.PromptsDelete
+---@field id string Prompt id
+
+---Arguments for `prompts.expand_mentions`: Replace `@prompt:<title-or-id>` mentions with prompt content (`@@prompt:` escapes).
+---@class AmpExtras.Args.PromptsExpandMentions
+---@field text string Message to expand; nested mentions expand up to 3 levels
+
+---Arguments for `prompts.import_from_directory`: Import `.md`/`.txt` files from a directory as prompts.
+---@class AmpExtras.Args.PromptsImportFromDirectory
+---@field path string Directory to scan (non-recursive)
+
+---Arguments for `prompts.list`: List prompts, optionally one page at a time.
+---@class AmpExtras.Args.PromptsList
+---@field cursor? string Opaque cursor from a previous page's `next_cursor`
+---@field page_size? integer Items per page (1-500, default 50)
+---@field sort? string `updated` (default), `most_used` or `recently_used`; unpaged only
+
+---Arguments for `prompts.mark_used`: Record a prompt use and return the updated prompt.
+---@class AmpExtras.Args.PromptsMarkUsed
+---@field id string Prompt id
+
+---Arguments for `prompts.recent`: Most recently used prompts.
+---@class AmpExtras.Args.PromptsRecent
+---@field limit? integer Maximum results (default 10)
+
+---Arguments for `prompts.search`: Full-text search over prompts, best matches first, optionally paged.
+---@class AmpExtras.Args.PromptsSearch
+---@field query string Search words; the last word matches as a prefix
+---@field limit? integer Maximum results (default 50); unpaged only
+---@field cursor? string Opaque cursor from a previous page's `next_cursor`
+---@field page_size? integer Items per page (1-500, default 50)
+
+---Arguments for `prompts.update`: Replace a prompt's fields.
+---@class AmpExtras.Args.PromptsUpdate
+---@field id string Prompt id
+---@field title string Display title
+---@field description? string Optional description
+---@field content string Prompt text
+---@field tags? string[] Tags
+
+---Arguments for `prompts.use`: Record a prompt use in the background.
+---@class AmpExtras.Args.PromptsUse
+---@field id string Prompt id
+
+---Arguments for `threads.create`: Start a new Amp thread; its first message runs in the background (see `amp.result`).
+---@class AmpExtras.Args.ThreadsCreate
+---@field message string First message of the thread
+---@field cwd? string Working directory for the CLI
+
+---Arguments for `threads.storage_info`: Resolved Amp thread directory, how it was found, and its file count and size.
+---@class AmpExtras.Args.ThreadsStorageInfo
@@ -3,3 +3,4 @@
 //! These tests run in a real Neovim instance using nvim-oxi's test framework.
 
 mod commands;
+mod send;
@@ -0,0 +1,311 @@
+//! Integration tests for the Lua send and compose commands
+//!
+//! The plugin's Lua modules are loaded from the repository and amp.nvim's
+//! `amp.message` is stubbed to record what would be sent: prompt appends in
+//! `_G.amp_sent`, submitted messages in `_G.amp_submitted`.
+//! Without the core library loaded, templates and lint fall back to the
+//! defaults (see `render` and `lint.check` in the Lua modules).
+
+use nvim_oxi::api;
+use serde_json::{json, Value};
+
+/// Run a Lua function body and decode its return value
+fn lua(body: &str) -> Value {
+    let expr = format!("vim.json.encode((function() {} end)())", body);
+    let encoded: String = api::call_function("luaeval", (expr,)).unwrap();
+    serde_json::from_str(&encoded).unwrap()
+}
+
+/// Put the plugin on the runtimepath, stub `amp.message` and open
+/// `<tmpdir>/sample.lua` as the current buffer (the tmpdir is the cwd)
+fn setup() {
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+    lua(&format!(
+        r#"
+        vim.opt.rtp:prepend([[{root}]])
+        _G.amp_sent = {{}}
+        _G.amp_submitted = {{}}
+        package.loaded["amp.message"] = {{
+          send_to_prompt = function(text) table.insert(_G.amp_sent, text) end,
+          send_message = function(text) table.insert(_G.amp_submitted, text) end,
+        }}
+
+        local dir = vim.fn.resolve(vim.fn.tempname())
+        vim.fn.mkdir(dir, "p")
+        vim.cmd.cd(dir)
+        vim.cmd.edit(dir .. "/sample.lua")
+        vim.bo.filetype = "lua"
+        vim.api.nvim_buf_set_lines(0, 0, -1, false, {{
+          "local a = 1",
+          "local b = 2",
+          "local c = 3",
+        }})
+        return true
+        "#
+    ));
+}
+
+/// Texts appended to the prompt through the `amp.message` stub
+fn sent() -> Value {
+    lua("return _G.amp_sent")
+}
+
+/// Messages submitted through the `amp.message` stub
+fn submitted() -> Value {
+    lua("return _G.amp_submitted")
+}
+
+/// Register the plugin's user commands
+///
+/// The UI and DashX modules need nui.nvim and the core library, so they are
+/// stubbed; the commands under test are registered as in the plugin.
+fn register_commands() {
+    lua(r#"
+        package.loaded["amp_extras.commands.ui"] = { send_message = {}, login = {} }
+        package.loaded["amp_extras.commands.dashx"] = { setup = function() end }
+        require("amp_extras.commands").register_commands()
+        return true
+    "#);
+}
+
+/// Number of `amp://compose` buffers
+fn compose_buffers() -> Value {
+    lua(r#"
+        local count = 0
+        for _, bufnr in ipairs(vim.api.nvim_list_bufs()) do
+          if vim.api.nvim_buf_get_name(bufnr) == "amp://compose" then
+            count = count + 1
+          end
+        end
+        return count
+    "#)
+}
+
+#[nvim_oxi::test]
+fn test_send_on_yank_linewise() {
+    setup();
+    lua(r#"
+        require("amp_extras.commands.send").setup_send_on_yank({ enabled = true, register = "a" })
+        vim.cmd('normal! gg"ayj')
+        return true
+    "#);
+
+    assert_eq!(
+        sent(),
+        json!(["@sample.lua#L1-2\n```lua\nlocal a = 1\nlocal b = 2\n```"])
+    );
+}
+
+#[nvim_oxi::test]
+fn test_send_on_yank_charwise_and_blockwise() {
+    setup();
+    lua(r#"
+        require("amp_extras.commands.send").setup_send_on_yank({ enabled = true, register = "a" })
+        vim.cmd('normal! gg0"aye')
+        vim.cmd('normal! gg0\22je"ay')
+        return true
+    "#);
+
+    assert_eq!(
+        sent(),
+        json!([
+            "@sample.lua#L1\n```lua\nlocal\n```",
+            "@sample.lua#L1-2\n```lua\nlocal\nlocal\n```",
+        ])
+    );
+}
+
+#[nvim_oxi::test]
+fn test_send_on_yank_register_filter() {
+    setup();
+    lua(r#"
+        require("amp_extras.commands.send").setup_send_on_yank({ enabled = true, register = "a" })
+        vim.cmd("normal! ggyy")
+        vim.cmd('normal! gg"byy')
+        -- Appending to the register counts as a yank into it; only the
+        -- appended line is sent
+        vim.fn.setreg("a", { "earlier" }, "V")
+        vim.cmd('normal! G"Ayy')
+        return true
+    "#);
+
+    assert_eq!(sent(), json!(["@sample.lua#L3\n```lua\nlocal c = 3\n```"]));
+}
+
+#[nvim_oxi::test]
+fn test_send_on_yank_any_register() {
+    setup();
+    lua(r#"
+        require("amp_extras.commands.send").setup_send_on_yank({ enabled = true, register = "" })
+        vim.cmd("normal! ggyy")
+        vim.cmd('normal! G"byy')
+        return true
+    "#);
+
+    assert_eq!(sent().as_array().unwrap().len(), 2);
+}
+
+#[nvim_oxi::test]
+fn test_send_on_yank_disabled() {
+    setup();
+    lua(r#"
+        local send = require("amp_extras.commands.send")
+        send.setup_send_on_yank({ enabled = true, register = "" })
+        send.setup_send_on_yank({ enabled = false })
+        vim.cmd("normal! ggyy")
+        return true
+    "#);
+
+    assert_eq!(sent(), json!([]));
+}
+
+#[nvim_oxi::test]
+fn test_ref_path_inside_and_outside_cwd() {
+    setup();
+    let paths = lua(r#"
+        local send = require("amp_extras.commands.send")
+        local inside, inside_absolute = send.ref_path(vim.api.nvim_buf_get_name(0))
+        local outside_file = vim.fn.resolve(vim.fn.tempname()) .. ".lua"
+        local outside, outside_absolute = send.ref_path(outside_file)
+        return {
+          inside = inside,
+          inside_absolute = inside_absolute,
+          outside_matches = outside == outside_file,
+          outside_absolute = outside_absolute,
+        }
+    "#);
+
+    assert_eq!(
+        paths,
+        json!({
+            "inside": "sample.lua",
+            "inside_absolute": false,
+            "outside_matches": true,
+            "outside_absolute": true,
+        })
+    );
+}
+
+#[nvim_oxi::test]
+fn test_selection_ref_outside_cwd_uses_absolute_path() {
+    setup();
+    let expected = lua(r##"
+        local file = vim.fn.resolve(vim.fn.tempname()) .. ".lua"
+        vim.fn.writefile({ "one", "two", "three" }, file)
+        vim.cmd.edit(file)
+        require("amp_extras.commands.send").send_selection_ref({ line1 = 2, line2 = 3 })
+        return "@" .. file .. "#L2-3"
+    "##);
+
+    assert_eq!(sent(), json!([expected]));
+}
+
+#[nvim_oxi::test]
+fn test_format_range_with_columns() {
+    setup();
+    let ranges = lua(r#"
+        local send = require("amp_extras.commands.send")
+        return {
+          send.format_range(10, 10, 5, 12),
+          send.format_range(10, 20, 5, 12),
+          send.format_range(10, 20),
+          send.format_range(10, 10),
+        }
+    "#);
+
+    assert_eq!(
+        ranges,
+        json!(["#L10:5-L10:12", "#L10:5-L20:12", "#L10-20", "#L10"])
+    );
+}
+
+#[nvim_oxi::test]
+fn test_selection_ref_columns_from_charwise_selection() {
+    setup();
+    lua(r#"
+        local send = require("amp_extras.commands.send")
+        -- "a" on line 1 through "b" on line 2, then a single-line selection
+        vim.cmd("normal! ggwvj\27")
+        send.send_selection_ref({ line1 = 1, line2 = 2 }, { columns = true })
+        vim.cmd("normal! ggwvee\27")
+        send.send_selection_ref({ line1 = 1, line2 = 1 }, { columns = true })
+        -- Linewise selections stay line-only
+        vim.cmd("normal! ggVj\27")
+        send.send_selection_ref({ line1 = 1, line2 = 2 }, { columns = true })
+        return true
+    "#);
+
+    assert_eq!(
+        sent(),
+        json!([
+            "@sample.lua#L1:7-L2:7",
+            "@sample.lua#L1:7-L1:11",
+            "@sample.lua#L1-2",
+        ])
+    );
+}
+
+#[nvim_oxi::test]
+fn test_compose_opens_one_buffer() {
+    setup();
+    register_commands();
+    lua(r#"
+        vim.cmd("AmpCompose")
+        vim.cmd("AmpCompose")
+        -- After :AmpReload the module (and its buffer handle) is gone but
+        -- the buffer isn't
+        vim.cmd("only")
+        package.loaded["amp_extras.commands.compose"] = nil
+        vim.cmd("AmpCompose")
+        return true
+    "#);
+
+    assert_eq!(compose_buffers(), json!(1));
+}
+
+#[nvim_oxi::test]
+fn test_compose_write_sends_message() {
+    setup();
+    register_commands();
+    lua(r#"
+        vim.cmd("AmpCompose")
+        vim.api.nvim_buf_set_lines(0, 0, -1, false, { "Explain this", "in detail" })
+        vim.cmd("write")
+        return true
+    "#);
+
+    assert_eq!(submitted(), json!(["Explain this\nin detail"]));
+    assert_eq!(sent(), json!([]));
+    assert_eq!(lua("return vim.bo.modified"), json!(false));
+}
+
+#[nvim_oxi::test]
+fn test_compose_write_bang_appends_to_prompt() {
+    setup();
+    register_commands();
+    lua(r#"
+        vim.cmd("AmpCompose")
+        vim.api.nvim_buf_set_lines(0, 0, -1, false, { "Draft" })
+        vim.cmd("write!")
+        return true
+    "#);
+
+    assert_eq!(sent(), json!(["Draft"]));
+    assert_eq!(submitted(), json!([]));
+}
+
+#[nvim_oxi::test]
+fn test_compose_rejects_empty_buffer() {
+    setup();
+    register_commands();
+    let sent_directly = lua(r#"
+        vim.cmd("AmpCompose")
+        vim.api.nvim_buf_set_lines(0, 0, -1, false, { "", "  " })
+        vim.cmd("write")
+        return require("amp_extras.commands.compose").send()
+    "#);
+
+    assert_eq!(sent_directly, json!(false));
+    assert_eq!(submitted(), json!([]));
+    assert_eq!(sent(), json!([]));
+}